@@ -0,0 +1,114 @@
+use crate::interval::Interval;
+use crate::ray::Ray;
+use crate::vec3::Point3;
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Aabb {
+    x: Interval,
+    y: Interval,
+    z: Interval,
+}
+
+impl Aabb {
+    pub fn new(x: Interval, y: Interval, z: Interval) -> Self {
+        Self { x, y, z }
+    }
+
+    // Flat axes are padded slightly so that boxes around planar geometry can still be hit.
+    pub fn from_points(a: Point3, b: Point3) -> Self {
+        const MIN_SIZE: f64 = 1e-4;
+        let pad = |interval: Interval| {
+            if interval.size() < MIN_SIZE {
+                interval.expand(MIN_SIZE)
+            } else {
+                interval
+            }
+        };
+
+        Self {
+            x: pad(Interval::new(a.x().min(b.x()), a.x().max(b.x()))),
+            y: pad(Interval::new(a.y().min(b.y()), a.y().max(b.y()))),
+            z: pad(Interval::new(a.z().min(b.z()), a.z().max(b.z()))),
+        }
+    }
+
+    pub fn surrounding(a: Aabb, b: Aabb) -> Self {
+        Self {
+            x: Interval::surrounding(a.x, b.x),
+            y: Interval::surrounding(a.y, b.y),
+            z: Interval::surrounding(a.z, b.z),
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self::new(Interval::empty(), Interval::empty(), Interval::empty())
+    }
+
+    pub fn universe() -> Self {
+        Self::new(
+            Interval::universe(),
+            Interval::universe(),
+            Interval::universe(),
+        )
+    }
+
+    pub fn x(self) -> Interval {
+        self.x
+    }
+
+    pub fn y(self) -> Interval {
+        self.y
+    }
+
+    pub fn z(self) -> Interval {
+        self.z
+    }
+
+    pub fn axis(self, n: usize) -> Interval {
+        match n {
+            1 => self.y,
+            2 => self.z,
+            _ => self.x,
+        }
+    }
+
+    pub fn min(self) -> Point3 {
+        Point3::new(self.x.min(), self.y.min(), self.z.min())
+    }
+
+    pub fn max(self) -> Point3 {
+        Point3::new(self.x.max(), self.y.max(), self.z.max())
+    }
+
+    pub fn hit(self, ray: Ray, ray_t: Interval) -> Option<Interval> {
+        let origin = [ray.origin().x(), ray.origin().y(), ray.origin().z()];
+        let direction = [
+            ray.direction().x(),
+            ray.direction().y(),
+            ray.direction().z(),
+        ];
+
+        let mut t_min = ray_t.min();
+        let mut t_max = ray_t.max();
+        for axis in 0..3 {
+            let inverse_direction = 1.0 / direction[axis];
+            let mut t0 = (self.axis(axis).min() - origin[axis]) * inverse_direction;
+            let mut t1 = (self.axis(axis).max() - origin[axis]) * inverse_direction;
+            if inverse_direction < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            if t0 > t_min {
+                t_min = t0;
+            }
+            if t1 < t_max {
+                t_max = t1;
+            }
+            if t_max <= t_min {
+                return None;
+            }
+        }
+
+        Some(Interval::new(t_min, t_max))
+    }
+}
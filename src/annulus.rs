@@ -0,0 +1,110 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::utility::{azimuth, degrees_to_radians};
+use crate::vec3::{Point3, Vec3};
+use std::sync::Arc;
+
+// A flat ring in the xz plane around `center`, facing +y and swept counterclockwise from +x.
+#[derive(Clone, Debug)]
+pub struct Annulus {
+    center: Point3,
+    inner_radius: f64,
+    outer_radius: f64,
+    phi_max: f64,
+    facing_down: bool,
+    material: Arc<dyn Material>,
+}
+
+impl Annulus {
+    pub fn new(
+        center: Point3,
+        inner_radius: f64,
+        outer_radius: f64,
+        sweep: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(sweep > 0.0);
+
+        Self {
+            center,
+            inner_radius,
+            outer_radius,
+            phi_max: degrees_to_radians(sweep.min(360.0)),
+            facing_down: false,
+            material,
+        }
+    }
+
+    pub(crate) fn facing_down(self) -> Self {
+        Self {
+            facing_down: true,
+            ..self
+        }
+    }
+
+    pub fn center(&self) -> Point3 {
+        self.center
+    }
+
+    pub fn inner_radius(&self) -> f64 {
+        self.inner_radius
+    }
+
+    pub fn outer_radius(&self) -> f64 {
+        self.outer_radius
+    }
+}
+
+impl Hittable for Annulus {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let oc = ray.origin() - self.center;
+        let direction = ray.direction();
+        if direction.y() == 0.0 {
+            return false;
+        }
+
+        let root = -oc.y() / direction.y();
+        if !ray_t.surrounds(root) {
+            return false;
+        }
+
+        let local = oc + direction * root;
+        let distance = (local.x() * local.x() + local.z() * local.z()).sqrt();
+        if distance < self.inner_radius || distance > self.outer_radius {
+            return false;
+        }
+
+        let phi = azimuth(local.x(), local.z());
+        if phi > self.phi_max {
+            return false;
+        }
+
+        let outward_normal = if self.facing_down {
+            Vec3::new(0.0, -1.0, 0.0)
+        } else {
+            Vec3::new(0.0, 1.0, 0.0)
+        };
+
+        record.t = root;
+        record.point = ray.at(root);
+        record.material = self.material.clone();
+        record.set_face_normal(ray, outward_normal);
+        record.tangent = Vec3::new(-local.z(), 0.0, local.x()).unit_vector();
+        record.u = phi / self.phi_max;
+        record.v = (self.outer_radius - distance) / (self.outer_radius - self.inner_radius);
+        record.uv_scale = (
+            self.phi_max * distance,
+            self.outer_radius - self.inner_radius,
+        );
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let extents = Vec3::new(self.outer_radius, 0.0, self.outer_radius);
+        Aabb::from_points(self.center - extents, self.center + extents)
+    }
+}
@@ -0,0 +1,478 @@
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::film::Film;
+use crate::hittable::{HitRecord, Hittable};
+use crate::integrator::{background, render_pixels, Integrator};
+use crate::interval::Interval;
+use crate::pdf::{CosinePdf, Pdf};
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::vec3::{Point3, Vec3};
+
+// Traces a subpath from the camera and another from a light for every sample, and joins every
+// prefix of one to every prefix of the other. Each way of building the same path is weighted by
+// multiple importance sampling with the power heuristic. When rendering, light subpaths are also
+// joined to a fresh point on the lens and splatted onto whichever pixel they land on.
+#[derive(Copy, Clone, Debug)]
+pub struct BidirectionalPathTracer {
+    max_depth: u32,
+}
+
+impl BidirectionalPathTracer {
+    pub fn new(max_depth: u32) -> Self {
+        Self { max_depth }
+    }
+
+    // Light arriving along `ray`. Also splats onto `film` the light that subpaths joined straight
+    // to the lens carry.
+    fn trace(&self, ray: Ray, scene: &Scene, camera: &Camera, film: &mut Film) -> Color {
+        let max_depth = self.max_depth as usize;
+
+        let pdf = camera.pdf_direction(ray.origin(), ray.direction());
+        let mut camera_path = vec![Vertex::camera(ray.origin())];
+        let mut color = random_walk(
+            scene,
+            ray,
+            Color::new(1.0, 1.0, 1.0),
+            pdf,
+            max_depth + 2,
+            &mut camera_path,
+        );
+        let light_path = light_subpath(scene, max_depth + 1);
+
+        for t in 2..=camera_path.len() {
+            for s in 0..=light_path.len() {
+                if s + t - 2 > max_depth {
+                    continue;
+                }
+
+                color += connect(scene, Some(camera), &camera_path, &light_path, s, t);
+            }
+        }
+
+        // A light vertex seen straight from the lens is left to camera rays that hit the light.
+        for s in 2..=light_path.len() {
+            let Some((lens, (x, y), contribution)) =
+                connect_to_camera(scene, camera, &light_path[s - 1])
+            else {
+                continue;
+            };
+
+            let weight = mis_weight(scene, Some(camera), &[lens], &light_path, s, 1);
+            film.add_splat(x, y, contribution * weight);
+        }
+
+        color
+    }
+}
+
+impl Integrator for BidirectionalPathTracer {
+    fn render(&self, camera: &Camera, scene: &Scene) -> Film {
+        render_pixels(camera, |ray, film| self.trace(ray, scene, camera, film))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum VertexKind {
+    Camera,
+    Light,
+    Scatter,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Vertex {
+    kind: VertexKind,
+    rec: HitRecord,
+    // The ray along which the subpath arrived; unused at the camera and light.
+    incoming: Ray,
+    beta: Color,
+    // Densities per unit area of sampling this vertex from its predecessor on its own subpath,
+    // and from its successor if the path were traced the other way.
+    pdf_fwd: f64,
+    pdf_rev: f64,
+    // Whether the vertex scattered specularly, so that no other strategy can create it.
+    delta: bool,
+}
+
+impl Vertex {
+    fn camera(point: Point3) -> Self {
+        Self {
+            kind: VertexKind::Camera,
+            rec: HitRecord {
+                point,
+                ..HitRecord::default()
+            },
+            incoming: Ray::default(),
+            beta: Color::new(1.0, 1.0, 1.0),
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+            delta: false,
+        }
+    }
+
+    fn point(&self) -> Point3 {
+        self.rec.point
+    }
+
+    fn is_on_surface(&self) -> bool {
+        match self.kind {
+            VertexKind::Camera => false,
+            VertexKind::Light => true,
+            VertexKind::Scatter => self.rec.material.is_surface(),
+        }
+    }
+
+    fn outward_normal(&self) -> Vec3 {
+        if self.rec.front_face {
+            self.rec.normal
+        } else {
+            -self.rec.normal
+        }
+    }
+
+    // Converts a solid angle density for leaving this vertex towards `next` into a density per
+    // unit area at `next`.
+    fn convert_density(&self, pdf: f64, next: &Vertex) -> f64 {
+        let offset = next.point() - self.point();
+        let distance_squared = offset.length_squared();
+        if distance_squared == 0.0 {
+            return 0.0;
+        }
+
+        let mut pdf = pdf / distance_squared;
+        if next.is_on_surface() {
+            pdf *= next.rec.normal.dot(offset).abs() / distance_squared.sqrt();
+        }
+
+        pdf
+    }
+
+    // Density per unit area at `next` of this vertex scattering towards it, having been reached
+    // from `prev`. A camera vertex only has a density when rendering through `camera`.
+    fn pdf(&self, camera: Option<&Camera>, prev: Option<&Vertex>, next: &Vertex) -> f64 {
+        match (self.kind, prev) {
+            (VertexKind::Camera, _) => camera.map_or(0.0, |camera| {
+                let pdf = camera.pdf_direction(self.point(), next.point() - self.point());
+                self.convert_density(pdf, next)
+            }),
+            (VertexKind::Light, _) => self.pdf_light(next),
+            (VertexKind::Scatter, Some(prev)) => {
+                let r_in = Ray::new(prev.point(), self.point() - prev.point());
+                let scattered = Ray::new(self.point(), next.point() - self.point());
+                let pdf = self.rec.material.pdf(&r_in, &self.rec, &scattered);
+                self.convert_density(pdf, next)
+            }
+            (VertexKind::Scatter, None) => 0.0,
+        }
+    }
+
+    // Density per unit area at `next` of light leaving this vertex towards it, with emitted
+    // directions sampled by cosine.
+    fn pdf_light(&self, next: &Vertex) -> f64 {
+        let pdf = CosinePdf::new(self.outward_normal()).value(next.point() - self.point());
+        self.convert_density(pdf, next)
+    }
+
+    fn emitted_toward(&self, target: Point3) -> Color {
+        let arriving = Ray::new(target, self.point() - target);
+        let mut rec = self.rec.clone();
+        rec.set_face_normal(arriving, self.outward_normal());
+        rec.material.emitted(&arriving, &rec)
+    }
+
+    // Light leaving this end of a light subpath towards `target`, including the cosine there.
+    fn light_toward(&self, target: Point3) -> Color {
+        let scattered = Ray::new(self.point(), target - self.point());
+        match self.kind {
+            VertexKind::Light => {
+                let cosine = self
+                    .outward_normal()
+                    .dot(scattered.direction().unit_vector())
+                    .abs();
+                self.emitted_toward(target) * cosine
+            }
+            _ => self
+                .rec
+                .material
+                .evaluate(&self.incoming, &self.rec, &scattered),
+        }
+    }
+}
+
+// Extends `path` from its last vertex along `ray` until the walk leaves the scene, is absorbed or
+// reaches `max_vertices`. Returns the background picked up if it leaves, weighted by the
+// throughput so far.
+fn random_walk(
+    scene: &Scene,
+    ray: Ray,
+    beta: Color,
+    pdf: f64,
+    max_vertices: usize,
+    path: &mut Vec<Vertex>,
+) -> Color {
+    let mut ray = ray;
+    let mut beta = beta;
+    let mut pdf_fwd = pdf;
+
+    while path.len() < max_vertices {
+        let mut rec = HitRecord::default();
+        if !scene
+            .world()
+            .hit(ray, Interval::new(0.001, f64::INFINITY), &mut rec)
+        {
+            return beta * background(ray);
+        }
+
+        let mut vertex = Vertex {
+            kind: VertexKind::Scatter,
+            rec: rec.clone(),
+            incoming: ray,
+            beta,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+            delta: false,
+        };
+        let prev = path.len() - 1;
+        vertex.pdf_fwd = path[prev].convert_density(pdf_fwd, &vertex);
+        path.push(vertex);
+        if path.len() >= max_vertices {
+            break;
+        }
+
+        let mut scattered = Ray::default();
+        let mut attenuation = Color::default();
+        if !rec
+            .material
+            .scatter(&ray, &rec, &mut attenuation, &mut scattered)
+        {
+            break;
+        }
+
+        // Specular bounces have no density, which the weights below treat as a special case.
+        let pdf = rec.material.pdf(&ray, &rec, &scattered);
+        let current = path.len() - 1;
+        let pdf_rev = if pdf <= 0.0 {
+            path[current].delta = true;
+            beta *= attenuation;
+            pdf_fwd = 0.0;
+            0.0
+        } else {
+            beta = beta * rec.material.evaluate(&ray, &rec, &scattered) / pdf;
+            pdf_fwd = pdf;
+
+            let reversed_in = Ray::new(scattered.at(1.0), -scattered.direction());
+            let reversed_out = Ray::new(rec.point, -ray.direction());
+            rec.material.pdf(&reversed_in, &rec, &reversed_out)
+        };
+        path[prev].pdf_rev = path[current].convert_density(pdf_rev, &path[prev]);
+
+        ray = scattered;
+    }
+
+    Color::zero()
+}
+
+pub(crate) fn light_subpath(scene: &Scene, max_vertices: usize) -> Vec<Vertex> {
+    let mut path = Vec::new();
+    let Some((rec, pdf_position)) = scene.lights().sample_surface() else {
+        return path;
+    };
+    if pdf_position <= 0.0 {
+        return path;
+    }
+
+    let light = Vertex {
+        kind: VertexKind::Light,
+        rec,
+        incoming: Ray::default(),
+        beta: Color::new(1.0, 1.0, 1.0) / pdf_position,
+        pdf_fwd: pdf_position,
+        pdf_rev: 0.0,
+        delta: false,
+    };
+
+    let normal = light.outward_normal();
+    let emission = CosinePdf::new(normal);
+    let direction = emission.generate();
+    let pdf_direction = emission.value(direction);
+    let emitted = light.emitted_toward(light.point() + direction);
+    let origin = light.point();
+    path.push(light);
+
+    if pdf_direction <= 0.0 || emitted.max_component() <= 0.0 {
+        return path;
+    }
+
+    let cosine = normal.dot(direction.unit_vector()).abs();
+    let beta = emitted * cosine / (pdf_position * pdf_direction);
+    random_walk(
+        scene,
+        Ray::new(origin, direction),
+        beta,
+        pdf_direction,
+        max_vertices,
+        &mut path,
+    );
+
+    path
+}
+
+// Light carried by the path made of the first `t` camera vertices and the first `s` light
+// vertices, already weighted against the other strategies that could have made it.
+fn connect(
+    scene: &Scene,
+    camera: Option<&Camera>,
+    camera_path: &[Vertex],
+    light_path: &[Vertex],
+    s: usize,
+    t: usize,
+) -> Color {
+    let pt = &camera_path[t - 1];
+
+    let contribution = if s == 0 {
+        if pt.kind != VertexKind::Scatter {
+            return Color::zero();
+        }
+
+        pt.beta * pt.rec.material.emitted(&pt.incoming, &pt.rec)
+    } else {
+        let qs = &light_path[s - 1];
+        if pt.delta || qs.delta {
+            return Color::zero();
+        }
+
+        let offset = qs.point() - pt.point();
+        let camera_side =
+            pt.rec
+                .material
+                .evaluate(&pt.incoming, &pt.rec, &Ray::new(pt.point(), offset));
+        let light_side = qs.light_toward(pt.point());
+        let contribution = qs.beta * light_side * camera_side * pt.beta / offset.length_squared();
+        if contribution.max_component() <= 0.0 {
+            return Color::zero();
+        }
+
+        contribution * transmittance(scene, pt.point(), qs.point())
+    };
+
+    if contribution.max_component() <= 0.0 {
+        return Color::zero();
+    }
+
+    contribution * mis_weight(scene, camera, camera_path, light_path, s, t)
+}
+
+// Joins the light subpath ending at `qs` to a random point on the lens. Returns the new camera
+// vertex, the raster position it sees `qs` at and the unweighted light carried, if `qs` is seen.
+pub(crate) fn connect_to_camera(
+    scene: &Scene,
+    camera: &Camera,
+    qs: &Vertex,
+) -> Option<(Vertex, (f64, f64), Color)> {
+    if qs.delta {
+        return None;
+    }
+
+    let lens = camera.sample_lens();
+    let (raster, importance) = camera.importance(lens, qs.point())?;
+    let contribution = qs.beta * qs.light_toward(lens) * importance;
+    if contribution.max_component() <= 0.0 {
+        return None;
+    }
+
+    let contribution = contribution * transmittance(scene, lens, qs.point());
+    if contribution.max_component() <= 0.0 {
+        return None;
+    }
+
+    Some((Vertex::camera(lens), raster, contribution))
+}
+
+// Fraction of light getting from `from` to `to`: zero if a surface is in the way, and the
+// transmittance of any media between them otherwise.
+fn transmittance(scene: &Scene, from: Point3, to: Point3) -> f64 {
+    let offset = to - from;
+    let distance = offset.length();
+
+    scene.world().transmittance(
+        Ray::new(from, offset / distance),
+        Interval::new(0.001, distance - 0.001),
+    )
+}
+
+// Power heuristic weight of the strategy with `s` light vertices and `t` camera vertices. Rather
+// than computing every strategy's density from scratch, this walks outwards from the connection
+// multiplying ratios of reverse to forward densities, as in pbrt.
+fn mis_weight(
+    scene: &Scene,
+    camera: Option<&Camera>,
+    camera_path: &[Vertex],
+    light_path: &[Vertex],
+    s: usize,
+    t: usize,
+) -> f64 {
+    if s + t == 2 {
+        return 1.0;
+    }
+
+    let densities = |vertex: &Vertex| (vertex.pdf_fwd, vertex.pdf_rev, vertex.delta);
+    let mut camera_densities: Vec<(f64, f64, bool)> =
+        camera_path[..t].iter().map(densities).collect();
+    let mut light: Vec<(f64, f64, bool)> = light_path[..s].iter().map(densities).collect();
+
+    // The vertices either side of the connection gain reverse densities that depend on it.
+    let pt = &camera_path[t - 1];
+    let pt_minus = (t >= 2).then(|| &camera_path[t - 2]);
+    if let (0, Some(pt_minus)) = (s, pt_minus) {
+        let origin_pdf = scene.lights().surface_pdf(pt.point());
+        // Emitters missing from the lights can only be found this way.
+        if origin_pdf <= 0.0 {
+            return 1.0;
+        }
+
+        camera_densities[t - 1].1 = origin_pdf;
+        camera_densities[t - 2].1 = pt.pdf_light(pt_minus);
+    } else {
+        let qs = &light_path[s - 1];
+        let qs_minus = (s >= 2).then(|| &light_path[s - 2]);
+
+        camera_densities[t - 1].1 = qs.pdf(camera, qs_minus, pt);
+        if let Some(pt_minus) = pt_minus {
+            camera_densities[t - 2].1 = pt.pdf(camera, Some(qs), pt_minus);
+        }
+        light[s - 1].1 = pt.pdf(camera, pt_minus, qs);
+        light[s - 1].2 = false;
+        if let Some(qs_minus) = qs_minus {
+            light[s - 2].1 = qs.pdf(camera, Some(pt), qs_minus);
+        }
+    }
+    camera_densities[t - 1].2 = false;
+
+    // Specular vertices have no density, and contribute a factor of one to the ratios. Squaring
+    // the rest makes the sum the power heuristic rather than the balance heuristic.
+    let remap = |pdf: f64| if pdf != 0.0 { pdf * pdf } else { 1.0 };
+
+    // Joining a light vertex straight to the camera is only done when splatting onto a film,
+    // and never for the light itself.
+    let light_tracing = camera.is_some() && s + t >= 3;
+
+    let mut sum = 0.0;
+    let mut ratio = 1.0;
+    for i in (1..t).rev() {
+        ratio *= remap(camera_densities[i].1) / remap(camera_densities[i].0);
+        if (i > 1 || light_tracing) && !camera_densities[i].2 && !camera_densities[i - 1].2 {
+            sum += ratio;
+        }
+    }
+
+    ratio = 1.0;
+    for i in (0..s).rev() {
+        ratio *= remap(light[i].1) / remap(light[i].0);
+        let delta_before = i > 0 && light[i - 1].2;
+        if !light[i].2 && !delta_before {
+            sum += ratio;
+        }
+    }
+
+    1.0 / (1.0 + sum)
+}
@@ -1,7 +1,6 @@
-use crate::color::Color;
-use crate::hittable::{HitRecord, Hittable};
-use crate::interval::Interval;
+use crate::integrator::Integrator;
 use crate::ray::Ray;
+use crate::scene::Scene;
 use crate::utility::{degrees_to_radians, random_double};
 use crate::vec3::{Point3, Vec3};
 
@@ -12,19 +11,24 @@ pub struct Camera {
     pixel00_location: Point3,
     pixel_delta_x: Vec3,
     pixel_delta_y: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    focus_dist: f64,
+    // Area of the image one unit in front of the lens.
+    film_area: f64,
     samples_per_pixel: u32,
-    max_depth: u32,
     defocus_angle: f64,
     defocus_disk_u: Vec3,
     defocus_disk_v: Vec3,
 }
 
 impl Camera {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         aspect_ratio: f64,
         image_width: u32,
         samples_per_pixel: u32,
-        max_depth: u32,
         vfov: f64,
         look_from: Point3,
         look_to: Point3,
@@ -66,41 +70,132 @@ impl Camera {
             pixel00_location,
             pixel_delta_x: pixel_delta_u,
             pixel_delta_y: pixel_delta_v,
+            u,
+            v,
+            w,
+            focus_dist,
+            film_area: viewport_width * viewport_height / (focus_dist * focus_dist),
             samples_per_pixel,
-            max_depth,
             defocus_angle,
             defocus_disk_u,
             defocus_disk_v,
         }
     }
 
-    pub fn render(&self, world: &dyn Hittable) {
-        print!("P3\n{} {}\n255\n", self.image_width, self.image_height);
-
-        for j in 0..self.image_height {
-            eprintln!("Scanlines remaining: {}", self.image_height - j);
-            for i in 0..self.image_width {
-                let mut pixel_color = Color::new(0.0, 0.0, 0.0);
-                for _ in 0..self.samples_per_pixel {
-                    let ray = self.get_ray(i, j);
-                    pixel_color += ray_color(ray, self.max_depth, world);
-                }
-                pixel_color.write_color(self.samples_per_pixel);
-            }
-        }
+    // Writes the image rendered by `integrator` to stdout as a PPM.
+    pub fn render(&self, scene: &Scene, integrator: &dyn Integrator) {
+        integrator.render(self, scene).write_ppm();
+    }
 
-        eprintln!("Done.");
+    pub fn image_width(&self) -> u32 {
+        self.image_width
     }
 
-    fn get_ray(&self, i: u32, j: u32) -> Ray {
-        let pixel_center = self.pixel00_location + self.pixel_delta_x * i + self.pixel_delta_y * j;
-        let pixel_sample = pixel_center + self.pixel_sample_square();
+    pub fn image_height(&self) -> u32 {
+        self.image_height
+    }
 
-        let ray_origin = if self.defocus_angle <= 0.0 {
+    pub fn samples_per_pixel(&self) -> u32 {
+        self.samples_per_pixel
+    }
+
+    // Continuous raster position of a world point seen through the center of the lens, or
+    // `None` if it is behind the camera.
+    pub fn project(&self, p: Point3) -> Option<(f64, f64)> {
+        self.raster_position(self.center, p - self.center)
+    }
+
+    // A random point on the lens, as camera rays leave from.
+    pub fn sample_lens(&self) -> Point3 {
+        if self.defocus_angle <= 0.0 {
             self.center
         } else {
             self.defocus_disk_sample()
+        }
+    }
+
+    // Raster position and importance of `p` seen from the point `lens` on the lens, or `None` if
+    // it's outside the image. The importance is divided by the density of the lens point and
+    // includes the falloff from `p` to the lens, so light leaving `p` towards the lens only needs
+    // multiplying by it to give its contribution to the pixel.
+    pub fn importance(&self, lens: Point3, p: Point3) -> Option<((f64, f64), f64)> {
+        let offset = p - lens;
+        let raster = self.image_position(lens, offset)?;
+
+        let distance_squared = offset.length_squared();
+        let cos_theta = -offset.unit_vector().dot(self.w);
+        let importance = 1.0 / (self.film_area * cos_theta.powi(3) * distance_squared);
+
+        Some((raster, importance))
+    }
+
+    // Density in solid angle with which camera rays from `lens` leave in `direction`.
+    pub fn pdf_direction(&self, lens: Point3, direction: Vec3) -> f64 {
+        if self.image_position(lens, direction).is_none() {
+            return 0.0;
+        }
+
+        let cos_theta = -direction.unit_vector().dot(self.w);
+        1.0 / (self.film_area * cos_theta.powi(3))
+    }
+
+    // Raster position where the ray from `lens` in `direction` crosses the plane in focus, or
+    // `None` if the ray points away from it.
+    fn raster_position(&self, lens: Point3, direction: Vec3) -> Option<(f64, f64)> {
+        let depth = -direction.dot(self.w);
+        if depth <= 0.0 {
+            return None;
+        }
+
+        let on_focus_plane = lens + direction * (self.focus_dist / depth);
+        let from_pixel00 = on_focus_plane - self.pixel00_location;
+        let x = from_pixel00.dot(self.u) / self.pixel_delta_x.length() + 0.5;
+        let y = from_pixel00.dot(-self.v) / self.pixel_delta_y.length() + 0.5;
+
+        Some((x, y))
+    }
+
+    // As `raster_position`, but only for rays that land on the image.
+    fn image_position(&self, lens: Point3, direction: Vec3) -> Option<(f64, f64)> {
+        let (x, y) = self.raster_position(lens, direction)?;
+        let width = f64::from(self.image_width);
+        let height = f64::from(self.image_height);
+
+        ((0.0..width).contains(&x) && (0.0..height).contains(&y)).then_some((x, y))
+    }
+
+    // Length in pixels of the segment between two world points once projected onto the image.
+    // Only the part in front of a near plane, a small fraction of the focus distance away, is
+    // measured, so segments reaching behind the camera have a finite length.
+    pub fn screen_space_length(&self, a: Point3, b: Point3) -> f64 {
+        let near = 1e-3 * self.focus_dist;
+        let depth = |p: Point3| -(p - self.center).dot(self.w);
+        let (depth_a, depth_b) = (depth(a), depth(b));
+        if depth_a < near && depth_b < near {
+            return 0.0;
+        }
+
+        let clip = |p: Point3, q: Point3, depth_p: f64, depth_q: f64| {
+            if depth_p >= near {
+                p
+            } else {
+                p + (q - p) * ((near - depth_p) / (depth_q - depth_p))
+            }
         };
+        let (start, end) = (clip(a, b, depth_a, depth_b), clip(b, a, depth_b, depth_a));
+
+        match (self.project(start), self.project(end)) {
+            (Some((ax, ay)), Some((bx, by))) => ((ax - bx).powi(2) + (ay - by).powi(2)).sqrt(),
+            _ => 0.0,
+        }
+    }
+
+    // A random ray through pixel (i, j), leaving from a random point on the lens.
+    pub fn get_ray(&self, i: u32, j: u32) -> Ray {
+        let pixel_center = self.pixel00_location + self.pixel_delta_x * i + self.pixel_delta_y * j;
+        let pixel_sample = pixel_center + self.pixel_sample_square();
+
+        let ray_origin = self.sample_lens();
 
         let ray_direction = pixel_sample - ray_origin;
 
@@ -118,27 +213,3 @@ impl Camera {
         self.pixel_delta_x * px + self.pixel_delta_y * py
     }
 }
-
-fn ray_color(r: Ray, depth: u32, world: &dyn Hittable) -> Color {
-    if depth <= 0 {
-        return Color::zero();
-    }
-
-    let mut rec = HitRecord::default();
-    if world.hit(r, Interval::new(0.001, f64::INFINITY), &mut rec) {
-        let mut scattered = Ray::default();
-        let mut attenuation = Color::default();
-        if rec
-            .material
-            .scatter(&r, &rec, &mut attenuation, &mut scattered)
-        {
-            return ray_color(scattered, depth - 1, world) * attenuation;
-        }
-
-        return Color::zero();
-    }
-
-    let unit_direction = r.direction().unit_vector();
-    let t = 0.5 * (unit_direction.y() + 1.0);
-    Color::new(1.0, 1.0, 1.0) * (1.0 - t) + Color::new(0.5, 0.7, 1.0) * t
-}
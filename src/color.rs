@@ -4,6 +4,11 @@ use crate::vec3::Vec3;
 pub type Color = Vec3;
 
 impl Color {
+    // Perceived brightness of a linear RGB color.
+    pub fn luminance(self) -> f64 {
+        0.2126 * self.x() + 0.7152 * self.y() + 0.0722 * self.z()
+    }
+
     pub fn write_color(self, samples_per_pixel: u32) {
         let mut r = self.x();
         let mut g = self.y();
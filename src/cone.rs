@@ -0,0 +1,176 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::utility::{azimuth, degrees_to_radians};
+use crate::vec3::{Point3, Vec3};
+use std::sync::Arc;
+
+// A cone with its base disk centered on `base` and its apex `height` above it along +y.
+#[derive(Clone, Debug)]
+pub struct Cone {
+    base: Point3,
+    radius: f64,
+    height: f64,
+    phi_max: f64,
+    capped: bool,
+    material: Arc<dyn Material>,
+}
+
+impl Cone {
+    pub fn new(
+        base: Point3,
+        radius: f64,
+        height: f64,
+        sweep: f64,
+        capped: bool,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(sweep > 0.0);
+
+        Self {
+            base,
+            radius,
+            height,
+            phi_max: degrees_to_radians(sweep.min(360.0)),
+            capped,
+            material,
+        }
+    }
+
+    pub fn base(&self) -> Point3 {
+        self.base
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    fn hit_side(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let oc = ray.origin() - self.base;
+        let direction = ray.direction();
+
+        let k = self.radius / self.height;
+        let k2 = k * k;
+        let apex_offset = self.height - oc.y();
+
+        let a = direction.x() * direction.x() + direction.z() * direction.z()
+            - k2 * direction.y() * direction.y();
+        let half_b =
+            oc.x() * direction.x() + oc.z() * direction.z() + k2 * apex_offset * direction.y();
+        let c = oc.x() * oc.x() + oc.z() * oc.z() - k2 * apex_offset * apex_offset;
+
+        let roots = if a.abs() < 1e-12 {
+            if half_b == 0.0 {
+                return false;
+            }
+            let root = -c / (2.0 * half_b);
+            [root, root]
+        } else {
+            let discriminant = half_b * half_b - a * c;
+            if discriminant < 0.0 {
+                return false;
+            }
+            let sqrtd = discriminant.sqrt();
+            let (r0, r1) = ((-half_b - sqrtd) / a, (-half_b + sqrtd) / a);
+            [r0.min(r1), r0.max(r1)]
+        };
+
+        for root in roots {
+            if !ray_t.surrounds(root) {
+                continue;
+            }
+
+            let local = oc + direction * root;
+            if local.y() < 0.0 || local.y() > self.height {
+                continue;
+            }
+
+            let phi = azimuth(local.x(), local.z());
+            if phi > self.phi_max {
+                continue;
+            }
+
+            let outward_normal =
+                Vec3::new(local.x(), k2 * (self.height - local.y()), local.z()).unit_vector();
+
+            record.t = root;
+            record.point = ray.at(root);
+            record.material = self.material.clone();
+            record.set_face_normal(ray, outward_normal);
+            record.tangent = Vec3::new(-local.z(), 0.0, local.x()).unit_vector();
+            record.u = phi / self.phi_max;
+            record.v = local.y() / self.height;
+            record.uv_scale = (
+                self.phi_max * (local.x() * local.x() + local.z() * local.z()).sqrt(),
+                self.height.hypot(self.radius),
+            );
+
+            return true;
+        }
+
+        false
+    }
+
+    fn hit_cap(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let oc = ray.origin() - self.base;
+        let direction = ray.direction();
+        if direction.y() == 0.0 {
+            return false;
+        }
+
+        let root = -oc.y() / direction.y();
+        if !ray_t.surrounds(root) {
+            return false;
+        }
+
+        let local = oc + direction * root;
+        let distance_squared = local.x() * local.x() + local.z() * local.z();
+        if distance_squared > self.radius * self.radius {
+            return false;
+        }
+
+        let phi = azimuth(local.x(), local.z());
+        if phi > self.phi_max {
+            return false;
+        }
+
+        record.t = root;
+        record.point = ray.at(root);
+        record.material = self.material.clone();
+        record.set_face_normal(ray, Vec3::new(0.0, -1.0, 0.0));
+        record.tangent = Vec3::new(-local.z(), 0.0, local.x()).unit_vector();
+        record.u = phi / self.phi_max;
+        record.v = distance_squared.sqrt() / self.radius;
+        record.uv_scale = (self.phi_max * distance_squared.sqrt(), self.radius);
+
+        true
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let mut hit_anything = self.hit_side(ray, ray_t, record);
+
+        if self.capped {
+            let closest_so_far = if hit_anything { record.t } else { ray_t.max() };
+            if self.hit_cap(ray, Interval::new(ray_t.min(), closest_so_far), record) {
+                hit_anything = true;
+            }
+        }
+
+        hit_anything
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::from_points(
+            self.base - Vec3::new(self.radius, 0.0, self.radius),
+            self.base + Vec3::new(self.radius, self.height, self.radius),
+        )
+    }
+}
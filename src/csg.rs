@@ -0,0 +1,214 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable, Span};
+use crate::interval::Interval;
+use crate::ray::Ray;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    fn inside(self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_left || in_right,
+            CsgOperation::Intersection => in_left && in_right,
+            CsgOperation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+// Combines two closed objects by merging the spans where the ray is inside each of them.
+pub struct Csg<A: Hittable, B: Hittable> {
+    left: A,
+    right: B,
+    operation: CsgOperation,
+}
+
+impl<A: Hittable, B: Hittable> Csg<A, B> {
+    pub fn new(left: A, right: B, operation: CsgOperation) -> Self {
+        Self {
+            left,
+            right,
+            operation,
+        }
+    }
+
+    pub fn union(left: A, right: B) -> Self {
+        Self::new(left, right, CsgOperation::Union)
+    }
+
+    pub fn intersection(left: A, right: B) -> Self {
+        Self::new(left, right, CsgOperation::Intersection)
+    }
+
+    pub fn difference(left: A, right: B) -> Self {
+        Self::new(left, right, CsgOperation::Difference)
+    }
+}
+
+struct Crossing {
+    record: HitRecord,
+    entering: bool,
+    from_left: bool,
+}
+
+impl<A: Hittable, B: Hittable> Hittable for Csg<A, B> {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        for span in self.spans(ray) {
+            for boundary in [span.enter, span.exit] {
+                if ray_t.surrounds(boundary.t) {
+                    *record = boundary;
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        match self.operation {
+            CsgOperation::Union => {
+                Aabb::surrounding(self.left.bounding_box(), self.right.bounding_box())
+            }
+            CsgOperation::Intersection | CsgOperation::Difference => self.left.bounding_box(),
+        }
+    }
+
+    fn spans(&self, ray: Ray) -> Vec<Span> {
+        let mut crossings = Vec::new();
+        for (spans, from_left) in [(self.left.spans(ray), true), (self.right.spans(ray), false)] {
+            for span in spans {
+                crossings.push(Crossing {
+                    record: span.enter,
+                    entering: true,
+                    from_left,
+                });
+                crossings.push(Crossing {
+                    record: span.exit,
+                    entering: false,
+                    from_left,
+                });
+            }
+        }
+        crossings.sort_by(|a, b| a.record.t.total_cmp(&b.record.t));
+
+        let mut spans = Vec::new();
+        let mut enter = None;
+        let mut in_left = false;
+        let mut in_right = false;
+
+        for crossing in crossings {
+            let was_inside = self.operation.inside(in_left, in_right);
+            if crossing.from_left {
+                in_left = crossing.entering;
+            } else {
+                in_right = crossing.entering;
+            }
+            let is_inside = self.operation.inside(in_left, in_right);
+
+            let mut record = crossing.record;
+            if self.operation == CsgOperation::Difference && !crossing.from_left {
+                // The subtracted object's surface faces the other way on the result, which
+                // flips the facing but leaves the ray-facing normal unchanged.
+                record.front_face = !record.front_face;
+            }
+
+            if !was_inside && is_inside {
+                enter = Some(record);
+            } else if was_inside && !is_inside {
+                if let Some(enter) = enter.take() {
+                    spans.push(Span {
+                        enter,
+                        exit: record,
+                    });
+                }
+            }
+        }
+
+        spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::sphere::Sphere;
+    use crate::vec3::{Point3, Vec3};
+    use std::sync::Arc;
+
+    // Unit spheres centered at x = 0 and x = `offset`.
+    fn spheres(offset: f64) -> (Sphere, Sphere) {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        (
+            Sphere::new(Point3::zero(), 1.0, material.clone()),
+            Sphere::new(Point3::new(offset, 0.0, 0.0), 1.0, material),
+        )
+    }
+
+    // Along the x axis from x = -5, so each span's distances are its x coordinates plus 5.
+    fn span_distances(csg: &impl Hittable) -> Vec<(f64, f64)> {
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        csg.spans(ray)
+            .iter()
+            .map(|span| (span.enter.t, span.exit.t))
+            .collect()
+    }
+
+    fn assert_spans(spans: Vec<(f64, f64)>, expected: &[(f64, f64)]) {
+        assert_eq!(spans.len(), expected.len(), "spans {spans:?}");
+        for (&(enter, exit), &(expected_enter, expected_exit)) in spans.iter().zip(expected) {
+            assert!(
+                (enter - expected_enter).abs() < 1e-9 && (exit - expected_exit).abs() < 1e-9,
+                "spans {spans:?}, expected {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn union_merges_overlapping_spans() {
+        let (left, right) = spheres(1.0);
+        assert_spans(span_distances(&Csg::union(left, right)), &[(4.0, 7.0)]);
+
+        let (left, right) = spheres(3.0);
+        assert_spans(
+            span_distances(&Csg::union(left, right)),
+            &[(4.0, 6.0), (7.0, 9.0)],
+        );
+    }
+
+    #[test]
+    fn intersection_keeps_the_overlap() {
+        let (left, right) = spheres(1.0);
+        assert_spans(
+            span_distances(&Csg::intersection(left, right)),
+            &[(5.0, 6.0)],
+        );
+
+        let (left, right) = spheres(3.0);
+        assert_spans(span_distances(&Csg::intersection(left, right)), &[]);
+    }
+
+    #[test]
+    fn difference_ends_on_the_subtracted_surface() {
+        let (left, right) = spheres(1.0);
+        let difference = Csg::difference(left, right);
+        assert_spans(span_distances(&difference), &[(4.0, 5.0)]);
+
+        // The ray leaves the result through the front of the subtracted sphere, which is a back
+        // face of the result, with its normal still against the ray.
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let exit = &difference.spans(ray)[0].exit;
+        assert!(!exit.front_face);
+        assert!((exit.normal - Vec3::new(-1.0, 0.0, 0.0)).near_zero());
+
+        let mut record = HitRecord::default();
+        assert!(difference.hit(ray, Interval::new(4.5, f64::INFINITY), &mut record));
+        assert!((record.t - 5.0).abs() < 1e-9 && !record.front_face);
+    }
+}
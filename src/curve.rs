@@ -0,0 +1,336 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::sync::Arc;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CurveType {
+    // A flat strip that always faces the incoming ray.
+    Flat,
+    // A flat strip shaded as if it were a round tube.
+    Cylinder,
+    // A flat strip turned to face along normals given at its end points, as made by
+    // `Curve::ribbon`. It narrows as it turns edge-on to the ray.
+    Ribbon,
+}
+
+// A cubic Bézier strand whose width is interpolated linearly from its start to its end.
+#[derive(Clone, Debug)]
+pub struct Curve {
+    control_points: [Point3; 4],
+    width: [f64; 2],
+    curve_type: CurveType,
+    // Unit normals at the start and end of a ribbon.
+    normals: Option<[Vec3; 2]>,
+    material: Arc<dyn Material>,
+}
+
+struct CurveHit {
+    t: f64,
+    u: f64,
+    v: f64,
+}
+
+impl Curve {
+    pub fn new(
+        control_points: [Point3; 4],
+        start_width: f64,
+        end_width: f64,
+        curve_type: CurveType,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert_ne!(
+            curve_type,
+            CurveType::Ribbon,
+            "ribbons are made by Curve::ribbon"
+        );
+
+        Self {
+            control_points,
+            width: [start_width, end_width],
+            curve_type,
+            normals: None,
+            material,
+        }
+    }
+
+    // A ribbon facing along `start_normal` at its first control point and `end_normal` at its
+    // last, turning between them at a steady rate.
+    pub fn ribbon(
+        control_points: [Point3; 4],
+        start_width: f64,
+        end_width: f64,
+        start_normal: Vec3,
+        end_normal: Vec3,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(!start_normal.near_zero() && !end_normal.near_zero());
+
+        Self {
+            control_points,
+            width: [start_width, end_width],
+            curve_type: CurveType::Ribbon,
+            normals: Some([start_normal.unit_vector(), end_normal.unit_vector()]),
+            material,
+        }
+    }
+
+    fn width_at(&self, u: f64) -> f64 {
+        lerp(u, self.width[0], self.width[1])
+    }
+
+    // Spherical interpolation between the end normals of a ribbon.
+    fn normal_at(&self, u: f64) -> Option<Vec3> {
+        let [start, end] = self.normals?;
+        let angle = start.dot(end).clamp(-1.0, 1.0).acos();
+        if angle.sin() < 1e-6 {
+            return Some(start);
+        }
+
+        Some((start * ((1.0 - u) * angle).sin() + end * (u * angle).sin()) / angle.sin())
+    }
+
+    // Subdivides the curve in a frame where the ray runs from the origin along +z, culling
+    // segments whose padded bounds miss the ray, then tests the leaf segments as line segments.
+    // `direction` is the ray's unit direction in world space.
+    fn intersect_recursive(
+        &self,
+        control_points: [Point3; 4],
+        u_range: (f64, f64),
+        depth: u32,
+        z_range: Interval,
+        direction: Vec3,
+    ) -> Option<CurveHit> {
+        if depth == 0 {
+            return self.intersect_segment(control_points, u_range, z_range, direction);
+        }
+
+        let split = subdivide_bezier(control_points);
+        let u_mid = 0.5 * (u_range.0 + u_range.1);
+        let halves = [
+            ([split[0], split[1], split[2], split[3]], (u_range.0, u_mid)),
+            ([split[3], split[4], split[5], split[6]], (u_mid, u_range.1)),
+        ];
+
+        let mut closest: Option<CurveHit> = None;
+        for (points, range) in halves {
+            let half_width = 0.5 * self.width_at(range.0).max(self.width_at(range.1));
+            let bounds = Aabb::from_points(points[0], points[1]);
+            let bounds = Aabb::surrounding(bounds, Aabb::from_points(points[2], points[3]));
+
+            if bounds.x().max() + half_width < 0.0
+                || bounds.x().min() - half_width > 0.0
+                || bounds.y().max() + half_width < 0.0
+                || bounds.y().min() - half_width > 0.0
+            {
+                continue;
+            }
+
+            let z_max = closest.as_ref().map_or(z_range.max(), |hit| hit.t);
+            if bounds.z().max() + half_width < z_range.min()
+                || bounds.z().min() - half_width > z_max
+            {
+                continue;
+            }
+
+            if let Some(hit) = self.intersect_recursive(
+                points,
+                range,
+                depth - 1,
+                Interval::new(z_range.min(), z_max),
+                direction,
+            ) {
+                closest = Some(hit);
+            }
+        }
+
+        closest
+    }
+
+    fn intersect_segment(
+        &self,
+        control_points: [Point3; 4],
+        u_range: (f64, f64),
+        z_range: Interval,
+        direction: Vec3,
+    ) -> Option<CurveHit> {
+        let [p0, p1, p2, p3] = control_points;
+
+        // Reject hits beyond the planes perpendicular to the segment at its end points.
+        let start_edge = (p1.y() - p0.y()) * -p0.y() + p0.x() * (p0.x() - p1.x());
+        let end_edge = (p2.y() - p3.y()) * -p3.y() + p3.x() * (p3.x() - p2.x());
+        if start_edge < 0.0 || end_edge < 0.0 {
+            return None;
+        }
+
+        let segment_x = p3.x() - p0.x();
+        let segment_y = p3.y() - p0.y();
+        let denominator = segment_x * segment_x + segment_y * segment_y;
+        if denominator == 0.0 {
+            return None;
+        }
+
+        let w = (-p0.x() * segment_x - p0.y() * segment_y) / denominator;
+        let u = lerp(w, u_range.0, u_range.1).clamp(u_range.0, u_range.1);
+        let mut hit_width = self.width_at(u);
+        if let Some(normal) = self.normal_at(u) {
+            hit_width *= normal.dot(direction).abs();
+        }
+
+        let (point, derivative) = evaluate_bezier(control_points, w.clamp(0.0, 1.0));
+        let distance_squared = point.x() * point.x() + point.y() * point.y();
+        if distance_squared > hit_width * hit_width * 0.25 {
+            return None;
+        }
+        if !z_range.surrounds(point.z()) {
+            return None;
+        }
+
+        let distance = distance_squared.sqrt();
+        let edge = derivative.x() * -point.y() + point.x() * derivative.y();
+        let v = if edge > 0.0 {
+            0.5 + distance / hit_width
+        } else {
+            0.5 - distance / hit_width
+        };
+
+        Some(CurveHit { t: point.z(), u, v })
+    }
+}
+
+impl Hittable for Curve {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let ray_length = ray.direction().length();
+        let ray_frame = Onb::new(ray.direction());
+        let to_ray_space = |p: Point3| {
+            let offset = p - ray.origin();
+            Vec3::new(
+                offset.dot(ray_frame.u()),
+                offset.dot(ray_frame.v()),
+                offset.dot(ray_frame.w()),
+            )
+        };
+        let control_points = self.control_points.map(to_ray_space);
+
+        // Enough subdivisions for the leaf segments to be within a fraction of the width of
+        // the true curve.
+        let mut flatness: f64 = 0.0;
+        for i in 0..2 {
+            let second_difference =
+                control_points[i] - control_points[i + 1] * 2.0 + control_points[i + 2];
+            flatness = flatness
+                .max(second_difference.x().abs())
+                .max(second_difference.y().abs())
+                .max(second_difference.z().abs());
+        }
+        let tolerance = self.width[0].max(self.width[1]) * 0.05;
+        let depth = if tolerance > 0.0 {
+            ((2.0_f64.sqrt() * 6.0 * flatness / (8.0 * tolerance)).log2() / 2.0).clamp(0.0, 10.0)
+                as u32
+        } else {
+            0
+        };
+
+        let z_range = Interval::new(ray_t.min() * ray_length, ray_t.max() * ray_length);
+        let Some(hit) = self.intersect_recursive(
+            control_points,
+            (0.0, 1.0),
+            depth,
+            z_range,
+            ray.direction() / ray_length,
+        ) else {
+            return false;
+        };
+
+        let (_, dpdu) = evaluate_bezier(self.control_points, hit.u);
+        let hit_width = self.width_at(hit.u);
+
+        let dpdv = if let Some(normal) = self.normal_at(hit.u) {
+            normal.cross(dpdu).unit_vector() * hit_width
+        } else {
+            let dpdu_plane = Vec3::new(
+                dpdu.dot(ray_frame.u()),
+                dpdu.dot(ray_frame.v()),
+                dpdu.dot(ray_frame.w()),
+            );
+            let mut dpdv_plane =
+                Vec3::new(-dpdu_plane.y(), dpdu_plane.x(), 0.0).unit_vector() * hit_width;
+            if self.curve_type == CurveType::Cylinder {
+                let theta = lerp(hit.v, -90.0_f64, 90.0).to_radians();
+                dpdv_plane = rotate(dpdv_plane, dpdu_plane.unit_vector(), -theta);
+            }
+            ray_frame.local(dpdv_plane.x(), dpdv_plane.y(), dpdv_plane.z())
+        };
+
+        record.t = hit.t / ray_length;
+        record.point = ray.at(record.t);
+        record.material = self.material.clone();
+        record.set_face_normal(ray, dpdu.cross(dpdv).unit_vector());
+        record.tangent = dpdu.unit_vector();
+        record.u = hit.u;
+        record.v = hit.v;
+        record.uv_scale = (dpdu.length(), dpdv.length());
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let [p0, p1, p2, p3] = self.control_points;
+        let bounds = Aabb::surrounding(Aabb::from_points(p0, p1), Aabb::from_points(p2, p3));
+        let padding = self.width[0].max(self.width[1]);
+
+        Aabb::new(
+            bounds.x().expand(padding),
+            bounds.y().expand(padding),
+            bounds.z().expand(padding),
+        )
+    }
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    (1.0 - t) * a + t * b
+}
+
+fn subdivide_bezier(p: [Point3; 4]) -> [Point3; 7] {
+    [
+        p[0],
+        (p[0] + p[1]) / 2.0,
+        (p[0] + p[1] * 2.0 + p[2]) / 4.0,
+        (p[0] + p[1] * 3.0 + p[2] * 3.0 + p[3]) / 8.0,
+        (p[1] + p[2] * 2.0 + p[3]) / 4.0,
+        (p[2] + p[3]) / 2.0,
+        p[3],
+    ]
+}
+
+// Evaluates the curve and its derivative at `u` with de Casteljau's algorithm.
+fn evaluate_bezier(p: [Point3; 4], u: f64) -> (Point3, Vec3) {
+    let lerp_point = |a: Point3, b: Point3| a * (1.0 - u) + b * u;
+
+    let level1 = [
+        lerp_point(p[0], p[1]),
+        lerp_point(p[1], p[2]),
+        lerp_point(p[2], p[3]),
+    ];
+    let level2 = [
+        lerp_point(level1[0], level1[1]),
+        lerp_point(level1[1], level1[2]),
+    ];
+
+    let derivative = if (level2[1] - level2[0]).length_squared() > 0.0 {
+        (level2[1] - level2[0]) * 3.0
+    } else {
+        p[3] - p[0]
+    };
+
+    (lerp_point(level2[0], level2[1]), derivative)
+}
+
+fn rotate(v: Vec3, axis: Vec3, angle: f64) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+    v * cos + axis.cross(v) * sin + axis * axis.dot(v) * (1.0 - cos)
+}
@@ -0,0 +1,179 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::utility::{azimuth, degrees_to_radians};
+use crate::vec3::{Point3, Vec3};
+use std::sync::Arc;
+
+// A cylinder standing on `base` along the +y axis, swept counterclockwise from +x by `sweep` degrees.
+#[derive(Clone, Debug)]
+pub struct Cylinder {
+    base: Point3,
+    radius: f64,
+    height: f64,
+    phi_max: f64,
+    capped: bool,
+    inward: bool,
+    material: Arc<dyn Material>,
+}
+
+impl Cylinder {
+    pub fn new(
+        base: Point3,
+        radius: f64,
+        height: f64,
+        sweep: f64,
+        capped: bool,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(sweep > 0.0);
+
+        Self {
+            base,
+            radius,
+            height,
+            phi_max: degrees_to_radians(sweep.min(360.0)),
+            capped,
+            inward: false,
+            material,
+        }
+    }
+
+    pub(crate) fn inward(self) -> Self {
+        Self {
+            inward: true,
+            ..self
+        }
+    }
+
+    pub fn base(&self) -> Point3 {
+        self.base
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    fn hit_side(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let oc = ray.origin() - self.base;
+        let direction = ray.direction();
+
+        let a = direction.x() * direction.x() + direction.z() * direction.z();
+        if a == 0.0 {
+            return false;
+        }
+        let half_b = oc.x() * direction.x() + oc.z() * direction.z();
+        let c = oc.x() * oc.x() + oc.z() * oc.z() - self.radius * self.radius;
+
+        let discriminant = half_b * half_b - a * c;
+        if discriminant < 0.0 {
+            return false;
+        }
+
+        let sqrtd = discriminant.sqrt();
+        for root in [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a] {
+            if !ray_t.surrounds(root) {
+                continue;
+            }
+
+            let local = oc + direction * root;
+            if local.y() < 0.0 || local.y() > self.height {
+                continue;
+            }
+
+            let phi = azimuth(local.x(), local.z());
+            if phi > self.phi_max {
+                continue;
+            }
+
+            let mut outward_normal = Vec3::new(local.x(), 0.0, local.z()) / self.radius;
+            if self.inward {
+                outward_normal = -outward_normal;
+            }
+
+            record.t = root;
+            record.point = ray.at(root);
+            record.material = self.material.clone();
+            record.set_face_normal(ray, outward_normal);
+            record.tangent = Vec3::new(-local.z(), 0.0, local.x()).unit_vector();
+            record.u = phi / self.phi_max;
+            record.v = local.y() / self.height;
+            record.uv_scale = (self.phi_max * self.radius, self.height);
+
+            return true;
+        }
+
+        false
+    }
+
+    fn hit_cap(&self, y: f64, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let oc = ray.origin() - self.base;
+        let direction = ray.direction();
+        if direction.y() == 0.0 {
+            return false;
+        }
+
+        let root = (y - oc.y()) / direction.y();
+        if !ray_t.surrounds(root) {
+            return false;
+        }
+
+        let local = oc + direction * root;
+        let distance_squared = local.x() * local.x() + local.z() * local.z();
+        if distance_squared > self.radius * self.radius {
+            return false;
+        }
+
+        let phi = azimuth(local.x(), local.z());
+        if phi > self.phi_max {
+            return false;
+        }
+
+        let outward_normal = if y > 0.0 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(0.0, -1.0, 0.0)
+        };
+
+        record.t = root;
+        record.point = ray.at(root);
+        record.material = self.material.clone();
+        record.set_face_normal(ray, outward_normal);
+        record.tangent = Vec3::new(-local.z(), 0.0, local.x()).unit_vector();
+        record.u = phi / self.phi_max;
+        record.v = distance_squared.sqrt() / self.radius;
+        record.uv_scale = (self.phi_max * distance_squared.sqrt(), self.radius);
+
+        true
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let mut hit_anything = self.hit_side(ray, ray_t, record);
+
+        if self.capped {
+            for y in [0.0, self.height] {
+                let closest_so_far = if hit_anything { record.t } else { ray_t.max() };
+                if self.hit_cap(y, ray, Interval::new(ray_t.min(), closest_so_far), record) {
+                    hit_anything = true;
+                }
+            }
+        }
+
+        hit_anything
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::from_points(
+            self.base - Vec3::new(self.radius, 0.0, self.radius),
+            self.base + Vec3::new(self.radius, self.height, self.radius),
+        )
+    }
+}
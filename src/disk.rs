@@ -0,0 +1,141 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable, SURFACE_TOLERANCE};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::utility::random_double;
+use crate::vec3::{Point3, Vec3};
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+#[derive(Clone, Debug)]
+pub struct Disk {
+    center: Point3,
+    normal: Vec3,
+    radius: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Disk {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, material: Arc<dyn Material>) -> Self {
+        Self {
+            center,
+            normal: normal.unit_vector(),
+            radius,
+            material,
+        }
+    }
+
+    pub fn center(&self) -> Point3 {
+        self.center
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        self.normal
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let denominator = self.normal.dot(ray.direction());
+        if denominator.abs() < 1e-8 {
+            return false;
+        }
+
+        let root = (self.center - ray.origin()).dot(self.normal) / denominator;
+        if !ray_t.surrounds(root) {
+            return false;
+        }
+
+        let point = ray.at(root);
+        let offset = point - self.center;
+        let distance_squared = offset.length_squared();
+        if distance_squared > self.radius * self.radius {
+            return false;
+        }
+
+        record.t = root;
+        record.point = point;
+        record.material = self.material.clone();
+        record.set_face_normal(ray, self.normal);
+
+        let basis = Onb::new(self.normal);
+        let phi = offset.dot(basis.v()).atan2(offset.dot(basis.u()));
+        record.tangent = basis.v() * phi.cos() - basis.u() * phi.sin();
+        record.u = (phi + PI) / (2.0 * PI);
+        record.v = distance_squared.sqrt() / self.radius;
+        record.uv_scale = (2.0 * PI * distance_squared.sqrt(), self.radius);
+
+        true
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let mut record = HitRecord::default();
+        if !self.hit(
+            Ray::new(origin, direction),
+            Interval::new(0.001, f64::INFINITY),
+            &mut record,
+        ) {
+            return 0.0;
+        }
+
+        let distance_squared = record.t * record.t * direction.length_squared();
+        let cosine = direction.dot(self.normal).abs() / direction.length();
+        let area = PI * self.radius * self.radius;
+
+        distance_squared / (cosine * area)
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        let basis = Onb::new(self.normal);
+        let r = self.radius * random_double().sqrt();
+        let phi = 2.0 * PI * random_double();
+
+        self.center + basis.local(r * phi.cos(), r * phi.sin(), 0.0) - origin
+    }
+
+    fn sample_surface(&self) -> Option<(HitRecord, f64)> {
+        let basis = Onb::new(self.normal);
+        let r = self.radius * random_double().sqrt();
+        let phi = 2.0 * PI * random_double() - PI;
+        let record = HitRecord {
+            point: self.center + basis.local(r * phi.cos(), r * phi.sin(), 0.0),
+            normal: self.normal,
+            tangent: basis.v() * phi.cos() - basis.u() * phi.sin(),
+            material: self.material.clone(),
+            u: (phi + PI) / (2.0 * PI),
+            v: r / self.radius,
+            uv_scale: (2.0 * PI * r, self.radius),
+            front_face: true,
+            ..HitRecord::default()
+        };
+
+        Some((record, 1.0 / (PI * self.radius * self.radius)))
+    }
+
+    fn surface_pdf(&self, point: Point3) -> f64 {
+        let offset = point - self.center;
+        let tolerance = SURFACE_TOLERANCE * self.radius;
+        if offset.dot(self.normal).abs() <= tolerance && offset.length() <= self.radius + tolerance
+        {
+            1.0 / (PI * self.radius * self.radius)
+        } else {
+            0.0
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let extent = |n: f64| self.radius * (1.0 - n * n).max(0.0).sqrt();
+        let extents = Vec3::new(
+            extent(self.normal.x()),
+            extent(self.normal.y()),
+            extent(self.normal.z()),
+        );
+        Aabb::from_points(self.center - extents, self.center + extents)
+    }
+}
@@ -0,0 +1,169 @@
+use crate::camera::Camera;
+use crate::material::Material;
+use crate::mesh::{edge_key, vertex_normals, Mesh, TriangleMesh};
+use crate::texture::Texture;
+use crate::vec3::{Point3, Vec3};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Controls how finely a mesh is diced before displacement: edges longer than
+// `max_edge_pixels` on screen are split, up to `max_level` times.
+#[derive(Copy, Clone, Debug)]
+pub struct TessellationSettings {
+    pub max_edge_pixels: f64,
+    pub max_level: u32,
+}
+
+impl Default for TessellationSettings {
+    fn default() -> Self {
+        Self {
+            max_edge_pixels: 2.0,
+            max_level: 8,
+        }
+    }
+}
+
+// Tessellates `mesh` adaptively for the view from `camera`, then offsets every vertex along its
+// smooth normal by `scale` times the displacement texture's first channel.
+pub fn displace(
+    mesh: &Mesh,
+    displacement: &dyn Texture,
+    scale: f64,
+    camera: &Camera,
+    settings: TessellationSettings,
+    material: Arc<dyn Material>,
+) -> TriangleMesh {
+    let mut tessellator = Tessellator::new(mesh, camera, settings);
+    for triangle in mesh.triangles() {
+        tessellator.tessellate(triangle, [0; 3]);
+    }
+
+    let Tessellator {
+        positions,
+        normals,
+        uvs,
+        triangles,
+        ..
+    } = tessellator;
+
+    let displaced: Vec<Point3> = positions
+        .iter()
+        .zip(&normals)
+        .zip(&uvs)
+        .map(|((&position, &normal), &(u, v))| {
+            position + normal * (scale * displacement.value(u, v, position).x())
+        })
+        .collect();
+    let displaced_normals = vertex_normals(&displaced, &triangles);
+
+    TriangleMesh::new(displaced, displaced_normals, uvs, triangles, material)
+}
+
+// Splits triangles edge by edge. Whether an edge is split depends only on its end points and
+// how many times it has already been halved, so triangles sharing an edge always agree and the
+// result stays free of cracks.
+struct Tessellator<'a> {
+    camera: &'a Camera,
+    settings: TessellationSettings,
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    midpoints: HashMap<(usize, usize), usize>,
+    triangles: Vec<[usize; 3]>,
+}
+
+impl<'a> Tessellator<'a> {
+    fn new(mesh: &Mesh, camera: &'a Camera, settings: TessellationSettings) -> Self {
+        let positions = mesh.positions().to_vec();
+        let normals = vertex_normals(&positions, &mesh.triangles());
+        let uvs = if mesh.uvs().is_empty() {
+            vec![(0.0, 0.0); positions.len()]
+        } else {
+            mesh.uvs().to_vec()
+        };
+
+        Self {
+            camera,
+            settings,
+            positions,
+            normals,
+            uvs,
+            midpoints: HashMap::new(),
+            triangles: Vec::new(),
+        }
+    }
+
+    fn midpoint(&mut self, a: usize, b: usize) -> usize {
+        if let Some(&index) = self.midpoints.get(&edge_key(a, b)) {
+            return index;
+        }
+
+        let normal = self.normals[a] + self.normals[b];
+        let (ua, va) = self.uvs[a];
+        let (ub, vb) = self.uvs[b];
+
+        self.positions
+            .push((self.positions[a] + self.positions[b]) / 2.0);
+        self.normals.push(if normal.near_zero() {
+            normal
+        } else {
+            normal.unit_vector()
+        });
+        self.uvs.push((0.5 * (ua + ub), 0.5 * (va + vb)));
+
+        let index = self.positions.len() - 1;
+        self.midpoints.insert(edge_key(a, b), index);
+        index
+    }
+
+    fn should_split(&self, a: usize, b: usize, level: u32) -> bool {
+        level < self.settings.max_level
+            && self
+                .camera
+                .screen_space_length(self.positions[a], self.positions[b])
+                > self.settings.max_edge_pixels
+    }
+
+    // `levels[i]` is how many times the edge from vertex i to vertex i + 1 has been halved.
+    fn tessellate(&mut self, triangle: [usize; 3], levels: [u32; 3]) {
+        let split =
+            [0, 1, 2].map(|i| self.should_split(triangle[i], triangle[(i + 1) % 3], levels[i]));
+        let inner = levels.iter().max().unwrap() + 1;
+
+        match split.iter().filter(|&&s| s).count() {
+            0 => self.triangles.push(triangle),
+            3 => {
+                let [a, b, c] = triangle;
+                let ab = self.midpoint(a, b);
+                let bc = self.midpoint(b, c);
+                let ca = self.midpoint(c, a);
+                let [l_ab, l_bc, l_ca] = levels.map(|l| l + 1);
+
+                self.tessellate([a, ab, ca], [l_ab, inner, l_ca]);
+                self.tessellate([ab, b, bc], [l_ab, l_bc, inner]);
+                self.tessellate([ca, bc, c], [inner, l_bc, l_ca]);
+                self.tessellate([ab, bc, ca], [inner; 3]);
+            }
+            count => {
+                // Rotate so that edge 0 (a to b) is split and, when two edges are split, edge 1
+                // (b to c) is the other one.
+                let rotation = (0..3)
+                    .find(|&r| split[r] && (count == 1 || split[(r + 1) % 3]))
+                    .unwrap();
+                let [a, b, c] = [0, 1, 2].map(|i| triangle[(i + rotation) % 3]);
+                let [l_ab, l_bc, l_ca] = [0, 1, 2].map(|i| levels[(i + rotation) % 3]);
+
+                let ab = self.midpoint(a, b);
+                if count == 1 {
+                    self.tessellate([a, ab, c], [l_ab + 1, inner, l_ca]);
+                    self.tessellate([ab, b, c], [l_ab + 1, l_bc, inner]);
+                } else {
+                    let bc = self.midpoint(b, c);
+                    self.tessellate([ab, b, bc], [l_ab + 1, l_bc + 1, inner]);
+                    self.tessellate([a, ab, bc], [l_ab + 1, inner, inner]);
+                    self.tessellate([a, bc, c], [inner, l_bc + 1, l_ca]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use crate::color::Color;
+
+// Accumulates the samples taken for each pixel of the image.
+#[derive(Clone, Debug)]
+pub struct Film {
+    width: u32,
+    height: u32,
+    samples_per_pixel: u32,
+    pixels: Vec<Color>,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32, samples_per_pixel: u32) -> Self {
+        Self {
+            width,
+            height,
+            samples_per_pixel,
+            pixels: vec![Color::zero(); (width * height) as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn add_sample(&mut self, i: u32, j: u32, color: Color) {
+        self.pixels[(i + j * self.width) as usize] += color;
+    }
+
+    // Adds light that landed at a continuous raster position, such as from a path traced from the
+    // lights, to the pixel containing it. It is averaged along with the pixel's samples.
+    pub fn add_splat(&mut self, x: f64, y: f64, color: Color) {
+        if x < 0.0 || y < 0.0 || x >= f64::from(self.width) || y >= f64::from(self.height) {
+            return;
+        }
+
+        self.add_sample(x as u32, y as u32, color);
+    }
+
+    // Mean of the averaged pixels over the whole image.
+    pub fn average(&self) -> Color {
+        let total = self
+            .pixels
+            .iter()
+            .fold(Color::zero(), |sum, &pixel| sum + pixel);
+        total / (f64::from(self.samples_per_pixel) * self.pixels.len() as f64)
+    }
+
+    // Writes the averaged pixels to stdout as a PPM image.
+    pub fn write_ppm(&self) {
+        print!("P3\n{} {}\n255\n", self.width, self.height);
+
+        for pixel in &self.pixels {
+            pixel.write_color(self.samples_per_pixel);
+        }
+    }
+}
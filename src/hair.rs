@@ -0,0 +1,230 @@
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::material::{tangent_frame, Material};
+use crate::ray::Ray;
+use crate::utility::{degrees_to_radians, random_double};
+use crate::vec3::Vec3;
+use std::f64::consts::PI;
+
+// Number of explicitly modeled scattering lobes (R, TT, TRT); higher-order scattering is
+// lumped into one extra isotropic-in-azimuth lobe.
+const P_MAX: usize = 3;
+
+const EUMELANIN_SIGMA_A: (f64, f64, f64) = (0.419, 0.697, 1.37);
+const PHEOMELANIN_SIGMA_A: (f64, f64, f64) = (0.187, 0.4, 1.05);
+
+// The d'Eon et al. / Chiang et al. hair scattering model. Directions are expressed in a frame
+// where x runs along the strand's tangent and the offset `h` across the strand's width comes
+// from the curve's v coordinate.
+#[derive(Copy, Clone, Debug)]
+pub struct HairBsdf {
+    sigma_a: Color,
+    eta: f64,
+    longitudinal_variance: [f64; P_MAX + 1],
+    azimuthal_scale: f64,
+    sin_2k_alpha: [f64; P_MAX],
+    cos_2k_alpha: [f64; P_MAX],
+}
+
+impl HairBsdf {
+    // `beta_m` and `beta_n` are the longitudinal and azimuthal roughness in [0, 1], and `alpha`
+    // is the cuticle scale tilt in degrees.
+    pub fn new(sigma_a: Color, beta_m: f64, beta_n: f64, alpha: f64) -> Self {
+        let v0 = (0.726 * beta_m + 0.812 * beta_m * beta_m + 3.7 * beta_m.powi(20)).powi(2);
+        let longitudinal_variance = [v0, 0.25 * v0, 4.0 * v0, 4.0 * v0];
+
+        let azimuthal_scale = (PI / 8.0).sqrt()
+            * (0.265 * beta_n + 1.194 * beta_n * beta_n + 5.372 * beta_n.powi(22));
+
+        let mut sin_2k_alpha = [0.0; P_MAX];
+        let mut cos_2k_alpha = [0.0; P_MAX];
+        sin_2k_alpha[0] = degrees_to_radians(alpha).sin();
+        cos_2k_alpha[0] = safe_sqrt(1.0 - sin_2k_alpha[0] * sin_2k_alpha[0]);
+        for i in 1..P_MAX {
+            sin_2k_alpha[i] = 2.0 * cos_2k_alpha[i - 1] * sin_2k_alpha[i - 1];
+            cos_2k_alpha[i] = cos_2k_alpha[i - 1].powi(2) - sin_2k_alpha[i - 1].powi(2);
+        }
+
+        Self {
+            sigma_a,
+            eta: 1.55,
+            longitudinal_variance,
+            azimuthal_scale,
+            sin_2k_alpha,
+            cos_2k_alpha,
+        }
+    }
+
+    // Absorption from eumelanin and pheomelanin concentrations, the pigments of natural hair.
+    pub fn from_melanin(
+        eumelanin: f64,
+        pheomelanin: f64,
+        beta_m: f64,
+        beta_n: f64,
+        alpha: f64,
+    ) -> Self {
+        let (er, eg, eb) = EUMELANIN_SIGMA_A;
+        let (pr, pg, pb) = PHEOMELANIN_SIGMA_A;
+        let sigma_a = Color::new(
+            eumelanin * er + pheomelanin * pr,
+            eumelanin * eg + pheomelanin * pg,
+            eumelanin * eb + pheomelanin * pb,
+        );
+
+        Self::new(sigma_a, beta_m, beta_n, alpha)
+    }
+
+    // Picks a lobe in proportion to its attenuation and samples its longitudinal and azimuthal
+    // distributions exactly, so the throughput is the lobe's attenuation over its probability.
+    pub fn sample(&self, r_in: &Ray, rec: &HitRecord) -> (Vec3, Color) {
+        // Off curves there is no fiber direction, so any tangent across the surface stands in.
+        let (x_axis, _, _) = tangent_frame(rec);
+        let z_axis = rec.normal;
+        let y_axis = z_axis.cross(x_axis);
+
+        let h = if rec.front_face {
+            -1.0 + 2.0 * rec.v
+        } else {
+            1.0 - 2.0 * rec.v
+        };
+
+        let wo = -r_in.direction().unit_vector();
+        let sin_theta_o = wo.dot(x_axis).clamp(-1.0, 1.0);
+        let cos_theta_o = safe_sqrt(1.0 - sin_theta_o * sin_theta_o);
+        let phi_o = wo.dot(z_axis).atan2(wo.dot(y_axis));
+
+        let sin_theta_t = sin_theta_o / self.eta;
+        let cos_theta_t = safe_sqrt(1.0 - sin_theta_t * sin_theta_t);
+        let etap = safe_sqrt(self.eta * self.eta - sin_theta_o * sin_theta_o) / cos_theta_o;
+        let sin_gamma_t = h / etap;
+        let cos_gamma_t = safe_sqrt(1.0 - sin_gamma_t * sin_gamma_t);
+        let gamma_t = sin_gamma_t.clamp(-1.0, 1.0).asin();
+        let gamma_o = h.clamp(-1.0, 1.0).asin();
+
+        let path_length = 2.0 * cos_gamma_t / cos_theta_t;
+        let transmittance = Color::new(
+            (-self.sigma_a.x() * path_length).exp(),
+            (-self.sigma_a.y() * path_length).exp(),
+            (-self.sigma_a.z() * path_length).exp(),
+        );
+        let attenuation = lobe_attenuation(cos_theta_o, self.eta, h, transmittance);
+
+        let weights = attenuation.map(luminance);
+        let total: f64 = weights.iter().sum();
+        let mut choice = random_double() * total;
+        let mut p = P_MAX;
+        for (lobe, &weight) in weights.iter().enumerate() {
+            if choice < weight {
+                p = lobe;
+                break;
+            }
+            choice -= weight;
+        }
+
+        let (sin_theta_op, cos_theta_op) = match p {
+            0 => (
+                sin_theta_o * self.cos_2k_alpha[1] - cos_theta_o * self.sin_2k_alpha[1],
+                cos_theta_o * self.cos_2k_alpha[1] + sin_theta_o * self.sin_2k_alpha[1],
+            ),
+            1 => (
+                sin_theta_o * self.cos_2k_alpha[0] + cos_theta_o * self.sin_2k_alpha[0],
+                cos_theta_o * self.cos_2k_alpha[0] - sin_theta_o * self.sin_2k_alpha[0],
+            ),
+            2 => (
+                sin_theta_o * self.cos_2k_alpha[2] + cos_theta_o * self.sin_2k_alpha[2],
+                cos_theta_o * self.cos_2k_alpha[2] - sin_theta_o * self.sin_2k_alpha[2],
+            ),
+            _ => (sin_theta_o, cos_theta_o),
+        };
+
+        let variance = self.longitudinal_variance[p];
+        let u = random_double().max(1e-5);
+        let cos_theta = 1.0 + variance * (u + (1.0 - u) * (-2.0 / variance).exp()).ln();
+        let sin_theta = safe_sqrt(1.0 - cos_theta * cos_theta);
+        let cos_phi = (2.0 * PI * random_double()).cos();
+        let sin_theta_i = -cos_theta * sin_theta_op + sin_theta * cos_phi * cos_theta_op;
+        let cos_theta_i = safe_sqrt(1.0 - sin_theta_i * sin_theta_i);
+
+        let delta_phi = if p < P_MAX {
+            2.0 * p as f64 * gamma_t - 2.0 * gamma_o
+                + p as f64 * PI
+                + sample_trimmed_logistic(random_double(), self.azimuthal_scale, -PI, PI)
+        } else {
+            2.0 * PI * random_double()
+        };
+        let phi_i = phi_o + delta_phi;
+
+        let direction = x_axis * sin_theta_i
+            + y_axis * (cos_theta_i * phi_i.cos())
+            + z_axis * (cos_theta_i * phi_i.sin());
+
+        (direction, attenuation[p] * (total / weights[p]))
+    }
+}
+
+impl Material for HairBsdf {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        attenuation: &mut Color,
+        scattered: &mut Ray,
+    ) -> bool {
+        let (direction, throughput) = self.sample(r_in, rec);
+        *scattered = Ray::new(rec.point, direction);
+        *attenuation = throughput;
+        true
+    }
+}
+
+fn lobe_attenuation(
+    cos_theta_o: f64,
+    eta: f64,
+    h: f64,
+    transmittance: Color,
+) -> [Color; P_MAX + 1] {
+    let cos_gamma_o = safe_sqrt(1.0 - h * h);
+    let f = fresnel_dielectric(cos_theta_o * cos_gamma_o, eta);
+    let white = Color::new(1.0, 1.0, 1.0);
+
+    let r = white * f;
+    let tt = transmittance * (1.0 - f) * (1.0 - f);
+    let trt = tt * transmittance * f;
+    let residual = trt * transmittance * f / (white - transmittance * f);
+
+    [r, tt, trt, residual]
+}
+
+fn fresnel_dielectric(cos_theta_i: f64, eta: f64) -> f64 {
+    let (cos_theta_i, eta) = if cos_theta_i < 0.0 {
+        (-cos_theta_i, 1.0 / eta)
+    } else {
+        (cos_theta_i, eta)
+    };
+    let cos_theta_i = cos_theta_i.min(1.0);
+
+    let sin_theta_t = safe_sqrt(1.0 - cos_theta_i * cos_theta_i) / eta;
+    if sin_theta_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_theta_t = safe_sqrt(1.0 - sin_theta_t * sin_theta_t);
+
+    let parallel = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let perpendicular = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+    0.5 * (parallel * parallel + perpendicular * perpendicular)
+}
+
+fn sample_trimmed_logistic(u: f64, s: f64, a: f64, b: f64) -> f64 {
+    let logistic_cdf = |x: f64| 1.0 / (1.0 + (-x / s).exp());
+    let k = logistic_cdf(b) - logistic_cdf(a);
+    let x = -s * (1.0 / (u * k + logistic_cdf(a)) - 1.0).ln();
+    x.clamp(a, b)
+}
+
+fn luminance(color: Color) -> f64 {
+    0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z()
+}
+
+fn safe_sqrt(x: f64) -> f64 {
+    x.max(0.0).sqrt()
+}
@@ -0,0 +1,278 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::image::Image;
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::triangle::intersect_triangle;
+use crate::vec3::{Point3, Vec3};
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+// A terrain of `resolution[0]` by `resolution[1]` height samples laid over the xz rectangle
+// starting at `origin`, with heights in [0, 1] scaled by `size.y()`.
+pub struct Heightfield {
+    resolution: [usize; 2],
+    heights: Vec<f64>,
+    normals: Vec<Vec3>,
+    origin: Point3,
+    size: Vec3,
+    material: Arc<dyn Material>,
+    bounding_box: Aabb,
+}
+
+impl Heightfield {
+    pub fn new(
+        resolution: [usize; 2],
+        heights: Vec<f64>,
+        origin: Point3,
+        size: Vec3,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(resolution[0] >= 2 && resolution[1] >= 2);
+        assert_eq!(heights.len(), resolution[0] * resolution[1]);
+
+        let heights: Vec<f64> = heights.into_iter().map(|h| h * size.y()).collect();
+        let (min_height, max_height) = heights
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &h| {
+                (min.min(h), max.max(h))
+            });
+
+        let bounding_box = Aabb::new(
+            Interval::new(origin.x(), origin.x() + size.x()),
+            Interval::new(origin.y() + min_height, origin.y() + max_height).expand(1e-4),
+            Interval::new(origin.z(), origin.z() + size.z()),
+        );
+
+        let mut heightfield = Self {
+            resolution,
+            heights,
+            normals: Vec::new(),
+            origin,
+            size,
+            material,
+            bounding_box,
+        };
+        heightfield.normals = heightfield.vertex_normals();
+        heightfield
+    }
+
+    // Loads heights from a grayscale netpbm image, with image rows running along +z.
+    pub fn from_pgm(
+        path: impl AsRef<Path>,
+        origin: Point3,
+        size: Vec3,
+        material: Arc<dyn Material>,
+    ) -> io::Result<Self> {
+        let image = Image::load(path)?;
+        if image.width() < 2 || image.height() < 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "heightfield image is too small",
+            ));
+        }
+
+        let mut heights = Vec::with_capacity(image.width() * image.height());
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                heights.push(image.pixel(x, y).x());
+            }
+        }
+
+        Ok(Self::new(
+            [image.width(), image.height()],
+            heights,
+            origin,
+            size,
+            material,
+        ))
+    }
+
+    fn cell_size(&self) -> (f64, f64) {
+        (
+            self.size.x() / (self.resolution[0] - 1) as f64,
+            self.size.z() / (self.resolution[1] - 1) as f64,
+        )
+    }
+
+    fn height(&self, x: usize, z: usize) -> f64 {
+        self.heights[x + z * self.resolution[0]]
+    }
+
+    fn vertex(&self, x: usize, z: usize) -> Point3 {
+        let (cell_width, cell_depth) = self.cell_size();
+        self.origin
+            + Vec3::new(
+                x as f64 * cell_width,
+                self.height(x, z),
+                z as f64 * cell_depth,
+            )
+    }
+
+    fn vertex_normals(&self) -> Vec<Vec3> {
+        let (cell_width, cell_depth) = self.cell_size();
+        let [nx, nz] = self.resolution;
+
+        let mut normals = Vec::with_capacity(nx * nz);
+        for z in 0..nz {
+            for x in 0..nx {
+                let (x0, x1) = (x.saturating_sub(1), (x + 1).min(nx - 1));
+                let (z0, z1) = (z.saturating_sub(1), (z + 1).min(nz - 1));
+
+                let slope_x =
+                    (self.height(x1, z) - self.height(x0, z)) / ((x1 - x0) as f64 * cell_width);
+                let slope_z =
+                    (self.height(x, z1) - self.height(x, z0)) / ((z1 - z0) as f64 * cell_depth);
+
+                normals.push(Vec3::new(-slope_x, 1.0, -slope_z).unit_vector());
+            }
+        }
+
+        normals
+    }
+
+    fn hit_cell(
+        &self,
+        x: usize,
+        z: usize,
+        ray: Ray,
+        ray_t: Interval,
+        record: &mut HitRecord,
+    ) -> bool {
+        let corners = [(x, z), (x + 1, z), (x + 1, z + 1), (x, z + 1)];
+        let mut hit_anything = false;
+        let mut closest_so_far = ray_t.max();
+
+        for triangle in [
+            [corners[0], corners[1], corners[2]],
+            [corners[0], corners[2], corners[3]],
+        ] {
+            let [a, b, c] = triangle.map(|(x, z)| self.vertex(x, z));
+            let Some((t, beta, gamma)) =
+                intersect_triangle(ray, a, b, c, Interval::new(ray_t.min(), closest_so_far))
+            else {
+                continue;
+            };
+
+            let [na, nb, nc] = triangle.map(|(x, z)| self.normals[x + z * self.resolution[0]]);
+            let normal = (na * (1.0 - beta - gamma) + nb * beta + nc * gamma).unit_vector();
+
+            closest_so_far = t;
+            hit_anything = true;
+
+            record.t = t;
+            record.point = ray.at(t);
+            record.material = self.material.clone();
+            record.set_face_normal(ray, normal);
+            record.tangent = Vec3::new(1.0, 0.0, 0.0);
+            record.u = (record.point.x() - self.origin.x()) / self.size.x();
+            record.v = (record.point.z() - self.origin.z()) / self.size.z();
+            record.uv_scale = (self.size.x(), self.size.z());
+        }
+
+        hit_anything
+    }
+}
+
+impl Hittable for Heightfield {
+    // Walks the cells under the ray with a 2D DDA, skipping cells whose height range the ray
+    // passes entirely above or below.
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let Some(segment) = self.bounding_box.hit(ray, ray_t) else {
+            return false;
+        };
+
+        let (cell_width, cell_depth) = self.cell_size();
+        let cells = [self.resolution[0] - 1, self.resolution[1] - 1];
+        let direction = ray.direction();
+        let entry = ray.at(segment.min()) - self.origin;
+
+        let cell_index = |offset: f64, size: f64, count: usize| {
+            ((offset / size).floor().max(0.0) as usize).min(count - 1)
+        };
+        let mut x = cell_index(entry.x(), cell_width, cells[0]);
+        let mut z = cell_index(entry.z(), cell_depth, cells[1]);
+
+        let next_boundary = |index: usize, offset: f64, size: f64, direction: f64| {
+            if direction > 0.0 {
+                segment.min() + ((index + 1) as f64 * size - offset) / direction
+            } else if direction < 0.0 {
+                segment.min() + (index as f64 * size - offset) / direction
+            } else {
+                f64::INFINITY
+            }
+        };
+        let mut t_next_x = next_boundary(x, entry.x(), cell_width, direction.x());
+        let mut t_next_z = next_boundary(z, entry.z(), cell_depth, direction.z());
+        let t_delta_x = cell_width / direction.x().abs();
+        let t_delta_z = cell_depth / direction.z().abs();
+
+        let mut t_cell_start = segment.min();
+        loop {
+            let t_cell_end = t_next_x.min(t_next_z).min(segment.max());
+
+            let corner_heights = [
+                self.height(x, z),
+                self.height(x + 1, z),
+                self.height(x, z + 1),
+                self.height(x + 1, z + 1),
+            ];
+            let cell_min = corner_heights.iter().copied().fold(f64::INFINITY, f64::min);
+            let cell_max = corner_heights
+                .iter()
+                .copied()
+                .fold(f64::NEG_INFINITY, f64::max);
+            let y_start = ray.at(t_cell_start).y() - self.origin.y();
+            let y_end = ray.at(t_cell_end).y() - self.origin.y();
+
+            if y_start.max(y_end) >= cell_min && y_start.min(y_end) <= cell_max {
+                let padding = 1e-9 * t_cell_end.abs().max(1.0);
+                let cell_t = Interval::new(
+                    ray_t.min().max(t_cell_start - padding),
+                    ray_t.max().min(t_cell_end + padding),
+                );
+                if self.hit_cell(x, z, ray, cell_t, record) {
+                    return true;
+                }
+            }
+
+            if t_cell_end >= segment.max() {
+                return false;
+            }
+
+            if t_next_x < t_next_z {
+                if direction.x() > 0.0 {
+                    x += 1;
+                } else if x == 0 {
+                    return false;
+                } else {
+                    x -= 1;
+                }
+                if x >= cells[0] {
+                    return false;
+                }
+                t_cell_start = t_next_x;
+                t_next_x += t_delta_x;
+            } else {
+                if direction.z() > 0.0 {
+                    z += 1;
+                } else if z == 0 {
+                    return false;
+                } else {
+                    z -= 1;
+                }
+                if z >= cells[1] {
+                    return false;
+                }
+                t_cell_start = t_next_z;
+                t_next_z += t_delta_z;
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
@@ -1,15 +1,27 @@
+use crate::aabb::Aabb;
 use crate::color::Color;
 use crate::interval::Interval;
-use crate::material::Material;
+use crate::material::{Lambertian, Material};
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
+use std::sync::{Arc, LazyLock};
 
-#[derive(Copy, Clone, Debug)]
+// Distance from a surface, relative to its size, within which a point counts as lying on it. This
+// allows for rounding in hit points.
+pub(crate) const SURFACE_TOLERANCE: f64 = 1e-6;
+
+#[derive(Clone, Debug)]
 pub struct HitRecord {
     pub point: Point3,
     pub normal: Vec3,
-    pub material: Material,
+    pub tangent: Vec3,
+    pub material: Arc<dyn Material>,
     pub t: f64,
+    pub u: f64,
+    pub v: f64,
+    // World distance covered by a unit change in u and in v, for effects measured per unit of
+    // texture space.
+    pub uv_scale: (f64, f64),
     pub front_face: bool,
 }
 
@@ -24,18 +36,104 @@ impl HitRecord {
     }
 }
 
+// Shared by every default record, so that clearing a record never allocates.
+static DEFAULT_MATERIAL: LazyLock<Arc<dyn Material>> =
+    LazyLock::new(|| Arc::new(Lambertian::new(Color::default())));
+
 impl Default for HitRecord {
     fn default() -> Self {
         Self {
             point: Point3::zero(),
             normal: Vec3::zero(),
-            material: Material::Lambertian(Color::default()),
+            tangent: Vec3::zero(),
+            material: DEFAULT_MATERIAL.clone(),
             t: 0.0,
+            u: 0.0,
+            v: 0.0,
+            uv_scale: (1.0, 1.0),
             front_face: false,
         }
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct Span {
+    pub enter: HitRecord,
+    pub exit: HitRecord,
+}
+
 pub trait Hittable {
     fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool;
+
+    fn bounding_box(&self) -> Aabb;
+
+    // Solid angle density, as seen from `origin`, of the directions `random` produces. Objects
+    // that can't be sampled as lights leave this at zero.
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    // A direction from `origin` towards a random point on the object.
+    fn random(&self, _origin: Point3) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+
+    // A random point on the surface, facing outwards, with its density per unit area. Lights
+    // that emit light paths need this.
+    fn sample_surface(&self) -> Option<(HitRecord, f64)> {
+        None
+    }
+
+    // Density per unit area with which `sample_surface` picks `point`, or zero if `point` isn't
+    // on the surface.
+    fn surface_pdf(&self, _point: Point3) -> f64 {
+        0.0
+    }
+
+    // Fraction of light along `ray` within `ray_t` that gets through the object. Anything hit is
+    // opaque, apart from cut-out parts of masked surfaces; media override this with a smoother
+    // estimate.
+    fn transmittance(&self, ray: Ray, ray_t: Interval) -> f64 {
+        let mut record = HitRecord::default();
+        let mut t_min = ray_t.min();
+        while self.hit(ray, Interval::new(t_min, ray_t.max()), &mut record) {
+            if !record.material.is_cut_out(&record) {
+                return 0.0;
+            }
+            t_min = record.t + 1e-7 * record.t.abs().max(1.0);
+        }
+
+        1.0
+    }
+
+    // Every interval along the whole ray where it is inside the object, in order. This is only
+    // meaningful for closed objects; the default walks successive hits and pairs entries with
+    // exits, which primitives with a closed-form solution should override.
+    fn spans(&self, ray: Ray) -> Vec<Span> {
+        const MAX_CROSSINGS: usize = 64;
+
+        let mut spans = Vec::new();
+        let mut enter = None;
+        let mut t_min = f64::NEG_INFINITY;
+        let mut record = HitRecord::default();
+
+        for _ in 0..MAX_CROSSINGS {
+            if !self.hit(ray, Interval::new(t_min, f64::INFINITY), &mut record) {
+                break;
+            }
+
+            if record.front_face {
+                enter.get_or_insert_with(|| record.clone());
+            } else if let Some(enter) = enter.take() {
+                spans.push(Span {
+                    enter,
+                    exit: record.clone(),
+                });
+            }
+
+            t_min = record.t + 1e-7 * record.t.abs().max(1.0);
+        }
+
+        spans
+    }
 }
@@ -1,21 +1,37 @@
+use crate::aabb::Aabb;
 use crate::hittable::{HitRecord, Hittable};
 use crate::interval::Interval;
 use crate::ray::Ray;
+use crate::utility::random_int;
+use crate::vec3::{Point3, Vec3};
 
 pub struct HittableList {
     objects: Vec<Box<dyn Hittable>>,
+    bounding_box: Aabb,
 }
 
 impl HittableList {
     pub fn new() -> Self {
         Self {
             objects: Vec::new(),
+            bounding_box: Aabb::empty(),
         }
     }
 
     pub fn add(&mut self, object: impl Hittable + 'static) {
+        self.bounding_box = Aabb::surrounding(self.bounding_box, object.bounding_box());
         self.objects.push(Box::new(object));
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+}
+
+impl Default for HittableList {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Hittable for HittableList {
@@ -25,17 +41,84 @@ impl Hittable for HittableList {
         let mut closest_so_far = ray_t.max();
 
         for object in self.objects.iter() {
-            if object.hit(
-                ray,
-                Interval::new(ray_t.min(), closest_so_far),
-                &mut temp_record,
-            ) {
+            // Hits on cut-out parts of a masked surface are stepped past, so the object is asked
+            // again for whatever lies behind them.
+            let mut t_min = ray_t.min();
+            while object.hit(ray, Interval::new(t_min, closest_so_far), &mut temp_record) {
+                if temp_record.material.is_cut_out(&temp_record) {
+                    t_min = temp_record.t + 1e-7 * temp_record.t.abs().max(1.0);
+                    continue;
+                }
+
                 hit_anything = true;
                 closest_so_far = temp_record.t;
-                *record = temp_record;
+                *record = temp_record.clone();
+                break;
             }
         }
 
         hit_anything
     }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+
+    fn transmittance(&self, ray: Ray, ray_t: Interval) -> f64 {
+        let mut transmittance = 1.0;
+        for object in self.objects.iter() {
+            transmittance *= object.transmittance(ray, ray_t);
+            if transmittance <= 0.0 {
+                return 0.0;
+            }
+        }
+
+        transmittance
+    }
+
+    // Picks one of the objects uniformly, so the density averages theirs.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+
+        let sum: f64 = self
+            .objects
+            .iter()
+            .map(|object| object.pdf_value(origin, direction))
+            .sum();
+        sum / self.objects.len() as f64
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        if self.objects.is_empty() {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+
+        let index = random_int(0, self.objects.len() as i32 - 1) as usize;
+        self.objects[index].random(origin)
+    }
+
+    fn sample_surface(&self) -> Option<(HitRecord, f64)> {
+        if self.objects.is_empty() {
+            return None;
+        }
+
+        let index = random_int(0, self.objects.len() as i32 - 1) as usize;
+        let (record, pdf) = self.objects[index].sample_surface()?;
+        Some((record, pdf / self.objects.len() as f64))
+    }
+
+    fn surface_pdf(&self, point: Point3) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+
+        let sum: f64 = self
+            .objects
+            .iter()
+            .map(|object| object.surface_pdf(point))
+            .sum();
+        sum / self.objects.len() as f64
+    }
 }
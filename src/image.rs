@@ -0,0 +1,129 @@
+use crate::color::Color;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// An image with channels normalized to [0, 1], as read from a netpbm file.
+#[derive(Clone, Debug)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert!(width > 0 && height > 0);
+        assert_eq!(Some(pixels.len()), width.checked_mul(height));
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // Loads plain (P2, P3) or binary (P5, P6) grayscale and color netpbm images.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let invalid =
+            |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+        let mut position = 0;
+        let mut header = Vec::new();
+        while header.len() < 4 {
+            while position < bytes.len() && bytes[position].is_ascii_whitespace() {
+                position += 1;
+            }
+            if position < bytes.len() && bytes[position] == b'#' {
+                while position < bytes.len() && bytes[position] != b'\n' {
+                    position += 1;
+                }
+                continue;
+            }
+
+            let start = position;
+            while position < bytes.len() && !bytes[position].is_ascii_whitespace() {
+                position += 1;
+            }
+            if start == position {
+                return Err(invalid("truncated netpbm header"));
+            }
+            header.push(String::from_utf8_lossy(&bytes[start..position]).into_owned());
+        }
+
+        let parse = |token: &str| {
+            token
+                .parse::<usize>()
+                .map_err(|_| invalid("malformed netpbm header"))
+        };
+        let width = parse(&header[1])?;
+        let height = parse(&header[2])?;
+        let max_value = parse(&header[3])?.max(1) as f64;
+
+        let (channels, binary) = match header[0].as_str() {
+            "P2" => (1, false),
+            "P3" => (3, false),
+            "P5" => (1, true),
+            "P6" => (3, true),
+            _ => return Err(invalid("unsupported netpbm format")),
+        };
+        if width == 0 || height == 0 {
+            return Err(invalid("netpbm image has no pixels"));
+        }
+        let count = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(channels))
+            .ok_or_else(|| invalid("netpbm header is too large"))?;
+
+        let samples: Vec<f64> = if binary {
+            let data = &bytes[(position + 1).min(bytes.len())..];
+            if max_value < 256.0 {
+                data.iter().take(count).map(|&b| b as f64).collect()
+            } else {
+                data.chunks_exact(2)
+                    .take(count)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as f64)
+                    .collect()
+            }
+        } else {
+            String::from_utf8_lossy(&bytes[position..])
+                .split_ascii_whitespace()
+                .take(count)
+                .map(|token| {
+                    token
+                        .parse::<f64>()
+                        .map_err(|_| invalid("malformed netpbm sample"))
+                })
+                .collect::<io::Result<_>>()?
+        };
+
+        if samples.len() != count {
+            return Err(invalid("truncated netpbm image"));
+        }
+
+        let pixels = samples
+            .chunks_exact(channels)
+            .map(|pixel| {
+                if channels == 1 {
+                    Color::new(pixel[0], pixel[0], pixel[0]) / max_value
+                } else {
+                    Color::new(pixel[0], pixel[1], pixel[2]) / max_value
+                }
+            })
+            .collect();
+
+        Ok(Self::new(width, height, pixels))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[x.min(self.width - 1) + y.min(self.height - 1) * self.width]
+    }
+}
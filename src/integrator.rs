@@ -0,0 +1,424 @@
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::film::Film;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::pdf::{CosinePdf, Pdf};
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::utility::random_double;
+
+const ROULETTE_MIN_BOUNCES: u32 = 3;
+
+// A rendering algorithm, which estimates the light reaching every pixel of the camera's image.
+pub trait Integrator {
+    fn render(&self, camera: &Camera, scene: &Scene) -> Film;
+}
+
+// An algorithm that says how much light arrives along each camera ray on its own, and so renders
+// the image one ray at a time.
+pub trait Radiance {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color;
+}
+
+impl<T: Radiance> Integrator for T {
+    fn render(&self, camera: &Camera, scene: &Scene) -> Film {
+        render_pixels(camera, |ray, _| self.radiance(ray, scene))
+    }
+}
+
+// Path tracer that only follows the directions materials scatter in, finding light by chance.
+#[derive(Copy, Clone, Debug)]
+pub struct NaivePathTracer {
+    max_depth: u32,
+}
+
+impl NaivePathTracer {
+    pub fn new(max_depth: u32) -> Self {
+        Self { max_depth }
+    }
+}
+
+impl Radiance for NaivePathTracer {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color {
+        let mut color = Color::zero();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = ray;
+
+        for bounce in 0..self.max_depth {
+            let mut rec = HitRecord::default();
+            if !scene
+                .world()
+                .hit(ray, Interval::new(0.001, f64::INFINITY), &mut rec)
+            {
+                color += throughput * background(ray);
+                break;
+            }
+
+            color += throughput * rec.material.emitted(&ray, &rec);
+
+            let Some((scattered, weight)) = scatter(&ray, &rec) else {
+                break;
+            };
+
+            let survival = survival_probability(bounce, throughput * weight);
+            if random_double() >= survival {
+                break;
+            }
+
+            throughput = throughput * weight / survival;
+            ray = scattered;
+        }
+
+        color
+    }
+}
+
+// Path tracer that samples the lights at every bounce that isn't perfectly specular, combining
+// them with the scattered rays by multiple importance sampling.
+#[derive(Copy, Clone, Debug)]
+pub struct PathTracer {
+    max_depth: u32,
+}
+
+impl PathTracer {
+    pub fn new(max_depth: u32) -> Self {
+        Self { max_depth }
+    }
+}
+
+impl Radiance for PathTracer {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color {
+        let lights = scene.lights();
+        let mut color = Color::zero();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = ray;
+        // Density with which the previous bounce scattered `ray`, if the lights were also
+        // sampled there; emission found by `ray` is then weighted against that light sample.
+        let mut bsdf_pdf: Option<f64> = None;
+
+        for bounce in 0..self.max_depth {
+            let mut rec = HitRecord::default();
+            if !scene
+                .world()
+                .hit(ray, Interval::new(0.001, f64::INFINITY), &mut rec)
+            {
+                color += throughput * background(ray);
+                break;
+            }
+
+            let mut emitted = rec.material.emitted(&ray, &rec);
+            if let Some(bsdf_pdf) = bsdf_pdf.filter(|_| !emitted.near_zero()) {
+                let light_pdf = lights.pdf_value(ray.origin(), ray.direction());
+                emitted = emitted * power_heuristic(bsdf_pdf, light_pdf);
+            }
+            color += throughput * emitted;
+
+            let mut scattered = Ray::default();
+            let mut attenuation = Color::default();
+            if !rec
+                .material
+                .scatter(&ray, &rec, &mut attenuation, &mut scattered)
+            {
+                break;
+            }
+
+            // Materials that cannot report a density for their samples, such as perfect mirrors,
+            // already fold the sample's weight into the attenuation and can't be lit directly.
+            let pdf = rec.material.pdf(&ray, &rec, &scattered);
+            let weight = if pdf <= 0.0 {
+                bsdf_pdf = None;
+                attenuation
+            } else if lights.is_empty() {
+                bsdf_pdf = None;
+                rec.material.evaluate(&ray, &rec, &scattered) / pdf
+            } else {
+                color += throughput * sample_lights(&ray, &rec, scene);
+                bsdf_pdf = Some(pdf);
+                rec.material.evaluate(&ray, &rec, &scattered) / pdf
+            };
+
+            let survival = survival_probability(bounce, throughput * weight);
+            if random_double() >= survival {
+                break;
+            }
+
+            throughput = throughput * weight / survival;
+            ray = scattered;
+        }
+
+        color
+    }
+}
+
+// Light that reaches the first non-specular surface seen straight from its source, through any
+// number of mirror and glass bounces on the way.
+#[derive(Copy, Clone, Debug)]
+pub struct DirectLighting {
+    max_depth: u32,
+}
+
+impl DirectLighting {
+    pub fn new(max_depth: u32) -> Self {
+        Self { max_depth }
+    }
+}
+
+impl Radiance for DirectLighting {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color {
+        let mut color = Color::zero();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = ray;
+
+        for _ in 0..self.max_depth {
+            let mut rec = HitRecord::default();
+            if !scene
+                .world()
+                .hit(ray, Interval::new(0.001, f64::INFINITY), &mut rec)
+            {
+                color += throughput * background(ray);
+                break;
+            }
+
+            color += throughput * rec.material.emitted(&ray, &rec);
+
+            let mut scattered = Ray::default();
+            let mut attenuation = Color::default();
+            if !rec
+                .material
+                .scatter(&ray, &rec, &mut attenuation, &mut scattered)
+            {
+                break;
+            }
+
+            let pdf = rec.material.pdf(&ray, &rec, &scattered);
+            if pdf <= 0.0 {
+                throughput *= attenuation;
+                ray = scattered;
+                continue;
+            }
+
+            color += throughput * estimate_direct(&ray, &rec, &scattered, pdf, scene);
+            break;
+        }
+
+        color
+    }
+}
+
+// Fraction of the hemisphere above the first hit left open within `distance`, weighted by cosine.
+#[derive(Copy, Clone, Debug)]
+pub struct AmbientOcclusion {
+    distance: f64,
+}
+
+impl AmbientOcclusion {
+    pub fn new(distance: f64) -> Self {
+        Self { distance }
+    }
+}
+
+impl Radiance for AmbientOcclusion {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color {
+        let mut rec = HitRecord::default();
+        if !scene
+            .world()
+            .hit(ray, Interval::new(0.001, f64::INFINITY), &mut rec)
+        {
+            return Color::new(1.0, 1.0, 1.0);
+        }
+
+        let occlusion_ray = Ray::new(rec.point, CosinePdf::new(rec.normal).generate());
+        let mut occluder = HitRecord::default();
+        if scene.world().hit(
+            occlusion_ray,
+            Interval::new(0.001, self.distance),
+            &mut occluder,
+        ) {
+            Color::zero()
+        } else {
+            Color::new(1.0, 1.0, 1.0)
+        }
+    }
+}
+
+// Shows a property of the first surface each camera ray hits, with black where rays miss.
+#[derive(Copy, Clone, Debug)]
+pub enum DebugView {
+    Normal,
+    Tangent,
+    Uv,
+}
+
+impl Radiance for DebugView {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color {
+        let mut rec = HitRecord::default();
+        if !scene
+            .world()
+            .hit(ray, Interval::new(0.001, f64::INFINITY), &mut rec)
+        {
+            return Color::zero();
+        }
+
+        match self {
+            DebugView::Normal => (rec.normal + 1.0) * 0.5,
+            DebugView::Tangent => (rec.tangent + 1.0) * 0.5,
+            DebugView::Uv => Color::new(rec.u, rec.v, 0.0),
+        }
+    }
+}
+
+// Renders every pixel of the camera's image by averaging `radiance` over its samples. The film is
+// passed along for integrators that also splat light onto other pixels.
+pub(crate) fn render_pixels(
+    camera: &Camera,
+    mut radiance: impl FnMut(Ray, &mut Film) -> Color,
+) -> Film {
+    let mut film = Film::new(
+        camera.image_width(),
+        camera.image_height(),
+        camera.samples_per_pixel(),
+    );
+
+    for j in 0..film.height() {
+        eprintln!("Scanlines remaining: {}", film.height() - j);
+        for i in 0..film.width() {
+            for _ in 0..camera.samples_per_pixel() {
+                let color = radiance(camera.get_ray(i, j), &mut film);
+                film.add_sample(i, j, color);
+            }
+        }
+    }
+
+    eprintln!("Done.");
+    film
+}
+
+// The direction and weight of a material's own sample, if it scatters at all.
+pub(crate) fn scatter(ray: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
+    let mut scattered = Ray::default();
+    let mut attenuation = Color::default();
+    if !rec
+        .material
+        .scatter(ray, rec, &mut attenuation, &mut scattered)
+    {
+        return None;
+    }
+
+    let pdf = rec.material.pdf(ray, rec, &scattered);
+    let weight = if pdf > 0.0 {
+        rec.material.evaluate(ray, rec, &scattered) / pdf
+    } else {
+        attenuation
+    };
+
+    Some((scattered, weight))
+}
+
+// Sky seen by rays that leave the scene.
+pub(crate) fn background(ray: Ray) -> Color {
+    let unit_direction = ray.direction().unit_vector();
+    let t = 0.5 * (unit_direction.y() + 1.0);
+    Color::new(1.0, 1.0, 1.0) * (1.0 - t) + Color::new(0.5, 0.7, 1.0) * t
+}
+
+// Light arriving directly from a random point on the lights, traced with a shadow ray and weighted
+// against the chance of the material scattering the same way.
+pub(crate) fn sample_lights(r: &Ray, rec: &HitRecord, scene: &Scene) -> Color {
+    let lights = scene.lights();
+    let direction = lights.random(rec.point);
+    let light_pdf = lights.pdf_value(rec.point, direction);
+    if light_pdf <= 0.0 {
+        return Color::zero();
+    }
+
+    let shadow_ray = Ray::new(rec.point, direction);
+    let bsdf = rec.material.evaluate(r, rec, &shadow_ray);
+    if bsdf.near_zero() {
+        return Color::zero();
+    }
+
+    // The light is found among the lights alone, and whatever lies in front of it only dims it.
+    let mut light_rec = HitRecord::default();
+    if !lights.hit(
+        shadow_ray,
+        Interval::new(0.001, f64::INFINITY),
+        &mut light_rec,
+    ) {
+        return Color::zero();
+    }
+
+    let transmittance = scene
+        .world()
+        .transmittance(shadow_ray, Interval::new(0.001, light_rec.t - 0.001));
+    if transmittance <= 0.0 {
+        return Color::zero();
+    }
+
+    let bsdf_pdf = rec.material.pdf(r, rec, &shadow_ray);
+    let weight = power_heuristic(light_pdf, bsdf_pdf);
+
+    bsdf * light_rec.material.emitted(&shadow_ray, &light_rec)
+        * (transmittance * weight / light_pdf)
+}
+
+// Light reaching a non-specular hit straight from its source: a sample of the lights, plus the
+// light found on the first hit of `scattered`, drawn by the material with density `pdf`.
+pub(crate) fn estimate_direct(
+    r: &Ray,
+    rec: &HitRecord,
+    scattered: &Ray,
+    pdf: f64,
+    scene: &Scene,
+) -> Color {
+    let lights = scene.lights();
+    let mut color = if lights.is_empty() {
+        Color::zero()
+    } else {
+        sample_lights(r, rec, scene)
+    };
+
+    let mut light_rec = HitRecord::default();
+    let found = if scene.world().hit(
+        *scattered,
+        Interval::new(0.001, f64::INFINITY),
+        &mut light_rec,
+    ) {
+        light_rec.material.emitted(scattered, &light_rec)
+    } else {
+        background(*scattered)
+    };
+
+    if !found.near_zero() {
+        let weight = rec.material.evaluate(r, rec, scattered) / pdf;
+        let light_pdf = lights.pdf_value(scattered.origin(), scattered.direction());
+        color += weight * found * power_heuristic(pdf, light_pdf);
+    }
+
+    color
+}
+
+// Chance of extending a path past its latest bounce. Paths are always extended for the first few
+// bounces, and afterwards in proportion to their throughput, so that dim paths end early while
+// the survivors are reweighted to keep the estimate unbiased.
+pub(crate) fn survival_probability(bounces: u32, throughput: Color) -> f64 {
+    if bounces < ROULETTE_MIN_BOUNCES {
+        return 1.0;
+    }
+
+    let brightest = throughput.max_component();
+    if brightest >= 1.0 {
+        1.0
+    } else {
+        brightest.min(0.95)
+    }
+}
+
+pub(crate) fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+    if a + b > 0.0 {
+        a / (a + b)
+    } else {
+        0.0
+    }
+}
@@ -23,6 +23,13 @@ impl Interval {
         }
     }
 
+    pub fn surrounding(a: Interval, b: Interval) -> Self {
+        Self {
+            min: a.min.min(b.min),
+            max: a.max.max(b.max),
+        }
+    }
+
     pub fn min(self) -> f64 {
         self.min
     }
@@ -31,6 +38,18 @@ impl Interval {
         self.max
     }
 
+    pub fn size(self) -> f64 {
+        self.max - self.min
+    }
+
+    pub fn expand(self, delta: f64) -> Self {
+        let padding = delta / 2.0;
+        Self {
+            min: self.min - padding,
+            max: self.max + padding,
+        }
+    }
+
     pub fn contains(self, x: f64) -> bool {
         self.min <= x && x <= self.max
     }
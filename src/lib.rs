@@ -0,0 +1,46 @@
+pub mod aabb;
+pub mod annulus;
+pub mod bdpt;
+pub mod camera;
+pub mod color;
+pub mod cone;
+pub mod csg;
+pub mod curve;
+pub mod cylinder;
+pub mod disk;
+pub mod displacement;
+pub mod film;
+pub mod hair;
+pub mod heightfield;
+pub mod hittable;
+pub mod hittable_list;
+pub mod image;
+pub mod integrator;
+pub mod interval;
+pub mod light_tracer;
+pub mod material;
+pub mod mesh;
+pub mod metaballs;
+pub mod mlt;
+pub mod onb;
+pub mod pdf;
+pub mod perlin;
+pub mod phase;
+pub mod photon;
+pub mod plane;
+pub mod polynomial;
+pub mod quad;
+pub mod ray;
+pub mod sampler;
+pub mod scene;
+pub mod scenes;
+pub mod sdf;
+pub mod sphere;
+pub mod subdivision;
+pub mod texture;
+pub mod torus;
+pub mod triangle;
+pub mod tube;
+pub mod utility;
+pub mod vec3;
+pub mod volume;
@@ -0,0 +1,44 @@
+use crate::bdpt::{connect_to_camera, light_subpath};
+use crate::camera::Camera;
+use crate::film::Film;
+use crate::integrator::Integrator;
+use crate::scene::Scene;
+
+// Traces paths from the lights alone, joining every vertex to a random point on the lens and
+// splatting the light it carries onto whichever pixel it lands on. Lights seen directly show up,
+// but mirrors and glass seen from the camera stay black, since light paths can only reach the
+// lens from a diffuse bounce, and the background is never found.
+#[derive(Copy, Clone, Debug)]
+pub struct LightTracer {
+    max_depth: u32,
+}
+
+impl LightTracer {
+    pub fn new(max_depth: u32) -> Self {
+        Self { max_depth }
+    }
+}
+
+impl Integrator for LightTracer {
+    // Traces as many light paths as the camera would trace rays.
+    fn render(&self, camera: &Camera, scene: &Scene) -> Film {
+        let passes = camera.samples_per_pixel();
+        let mut film = Film::new(camera.image_width(), camera.image_height(), passes);
+        let paths_per_pass = camera.image_width() * camera.image_height();
+
+        for pass in 0..passes {
+            eprintln!("Passes remaining: {}", passes - pass);
+            for _ in 0..paths_per_pass {
+                let path = light_subpath(scene, self.max_depth as usize + 1);
+                for vertex in &path {
+                    if let Some((_, (x, y), color)) = connect_to_camera(scene, camera, vertex) {
+                        film.add_splat(x, y, color);
+                    }
+                }
+            }
+        }
+
+        eprintln!("Done.");
+        film
+    }
+}
@@ -1,89 +1,112 @@
-use crate::camera::Camera;
-use crate::color::Color;
-use crate::hittable_list::HittableList;
-use crate::material::Material;
-use crate::utility::{random_double, random_double_range};
-use crate::vec3::{Point3, Vec3};
+use riaw::bdpt::BidirectionalPathTracer;
+use riaw::integrator::{
+    AmbientOcclusion, DebugView, DirectLighting, Integrator, NaivePathTracer, PathTracer,
+};
+use riaw::light_tracer::LightTracer;
+use riaw::mlt::MetropolisLightTransport;
+use riaw::photon::{PhotonMapper, ProgressivePhotonMapper};
+use riaw::sampler::seed;
+use riaw::scenes::{lit_room, random_spheres};
+use std::env;
+use std::process;
 
-mod camera;
-mod color;
-mod hittable;
-mod hittable_list;
-mod interval;
-mod material;
-mod ray;
-mod sphere;
-mod utility;
-mod vec3;
+const MAX_DEPTH: u32 = 200;
+// Bidirectional paths connect every pair of subpath vertices, so their cost grows with the square
+// of the depth.
+const BIDIRECTIONAL_MAX_DEPTH: u32 = 16;
+const PHOTON_COUNT: usize = 200_000;
+const PHOTON_RADIUS: f64 = 0.05;
+const PROGRESSIVE_ITERATIONS: u32 = 64;
+// Fraction of each iteration's photons kept by progressive photon mapping as gathering radii
+// shrink.
+const PROGRESSIVE_ALPHA: f64 = 0.7;
+const METROPOLIS_BOOTSTRAP_SAMPLES: u64 = 100_000;
+const METROPOLIS_CHAINS: u64 = 1_000;
+// Standard deviation of small mutations in primary sample space, and how often a mutation
+// instead draws a fresh path.
+const METROPOLIS_SIGMA: f64 = 0.01;
+const METROPOLIS_LARGE_STEP_PROBABILITY: f64 = 0.3;
 
 fn main() {
-    let mut world = HittableList::new();
+    let args: Vec<String> = env::args().collect();
+    let name = flag(&args, "--integrator").unwrap_or("path");
+    let Some(integrator) = select_integrator(name) else {
+        eprintln!(
+            "Unknown integrator '{name}'; expected one of: path, bdpt, photon, sppm, mlt, light, naive, direct, ao, normal, tangent, uv"
+        );
+        process::exit(1);
+    };
 
-    let ground_material = Material::Lambertian(Color::new(0.5, 0.5, 0.5));
-    world.add(sphere::Sphere::new(
-        Point3::new(0.0, -1000.0, 0.0),
-        1000.0,
-        ground_material,
-    ));
-
-    for a in -11..11 {
-        for b in -11..11 {
-            let choose_material = random_double();
-            let center = Point3::new(
-                a as f64 + 0.9 * random_double(),
-                0.2,
-                b as f64 + 0.9 * random_double(),
-            );
-
-            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
-                let sphere_material = if choose_material < 0.8 {
-                    let albedo = Color::random() * Color::random();
-                    Material::Lambertian(albedo)
-                } else if choose_material < 0.95 {
-                    let albedo = Color::random_range(0.5, 1.0);
-                    let fuzz = random_double_range(0.0, 0.5);
-                    Material::Metal(albedo, fuzz)
-                } else {
-                    Material::Dielectric(1.5)
-                };
+    // Scenes draw from the sampler too, so seeding first makes both the scene and the render
+    // repeatable.
+    if let Some(value) = flag(&args, "--seed") {
+        let Ok(value) = value.parse() else {
+            eprintln!("Invalid seed '{value}'; expected a non-negative integer");
+            process::exit(1);
+        };
+        seed(value);
+    }
 
-                world.add(sphere::Sphere::new(center, 0.2, sphere_material));
-            }
+    let scene_name = flag(&args, "--scene").unwrap_or("spheres");
+    let (scene, camera) = match scene_name {
+        "spheres" => random_spheres(1200, 500),
+        "room" => lit_room(600, 500),
+        _ => {
+            eprintln!("Unknown scene '{scene_name}'; expected one of: spheres, room");
+            process::exit(1);
         }
+    };
+
+    // These integrators only carry light that leaves the sampled emitters, so without any they
+    // would render black.
+    if scene.lights().is_empty() && matches!(name, "photon" | "sppm" | "light") {
+        eprintln!("Integrator '{name}' needs a scene with lights, such as --scene room");
+        process::exit(1);
     }
 
-    let material1 = Material::Dielectric(1.5);
-    world.add(sphere::Sphere::new(
-        Point3::new(0.0, 1.0, 0.0),
-        1.0,
-        material1,
-    ));
+    camera.render(&scene, integrator.as_ref());
+}
 
-    let material2 = Material::Lambertian(Color::new(0.4, 0.2, 0.1));
-    world.add(sphere::Sphere::new(
-        Point3::new(-4.0, 1.0, 0.0),
-        1.0,
-        material2,
-    ));
+fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str)
+}
 
-    let material3 = Material::Metal(Color::new(0.7, 0.6, 0.5), 0.0);
-    world.add(sphere::Sphere::new(
-        Point3::new(4.0, 1.0, 0.0),
-        1.0,
-        material3,
-    ));
+fn select_integrator(name: &str) -> Option<Box<dyn Integrator>> {
+    let integrator: Box<dyn Integrator> = match name {
+        "path" => Box::new(PathTracer::new(MAX_DEPTH)),
+        "bdpt" => Box::new(BidirectionalPathTracer::new(BIDIRECTIONAL_MAX_DEPTH)),
+        "photon" => Box::new(PhotonMapper::new(
+            PHOTON_COUNT,
+            PHOTON_RADIUS,
+            MAX_DEPTH,
+            true,
+        )),
+        "sppm" => Box::new(ProgressivePhotonMapper::new(
+            PROGRESSIVE_ITERATIONS,
+            PHOTON_COUNT,
+            PHOTON_RADIUS,
+            PROGRESSIVE_ALPHA,
+            MAX_DEPTH,
+        )),
+        "light" => Box::new(LightTracer::new(MAX_DEPTH)),
+        "mlt" => Box::new(MetropolisLightTransport::new(
+            MAX_DEPTH,
+            METROPOLIS_BOOTSTRAP_SAMPLES,
+            METROPOLIS_CHAINS,
+            METROPOLIS_SIGMA,
+            METROPOLIS_LARGE_STEP_PROBABILITY,
+        )),
+        "naive" => Box::new(NaivePathTracer::new(MAX_DEPTH)),
+        "direct" => Box::new(DirectLighting::new(MAX_DEPTH)),
+        "ao" => Box::new(AmbientOcclusion::new(1.0)),
+        "normal" => Box::new(DebugView::Normal),
+        "tangent" => Box::new(DebugView::Tangent),
+        "uv" => Box::new(DebugView::Uv),
+        _ => return None,
+    };
 
-    let camera = Camera::new(
-        16.0 / 9.0,
-        1200,
-        500,
-        50,
-        20.0,
-        Point3::new(13.0, 2.0, 3.0),
-        Point3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 1.0, 0.0),
-        0.5,
-        10.0,
-    );
-    camera.render(&world);
+    Some(integrator)
 }
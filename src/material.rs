@@ -1,63 +1,526 @@
 use crate::color::Color;
 use crate::hittable::HitRecord;
+use crate::onb::Onb;
+use crate::pdf::{CosinePdf, Pdf};
+use crate::phase::PhaseFunction;
 use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::utility::random_double;
 use crate::vec3::Vec3;
+use std::f64::consts::PI;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+pub trait Material: Debug + Send + Sync {
+    // Samples a scattered ray, returning false if the ray is absorbed. The attenuation is the
+    // sample's weight: `evaluate` over `pdf` for the sampled direction, or the reflectance of a
+    // perfectly specular bounce for which neither is defined.
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        attenuation: &mut Color,
+        scattered: &mut Ray,
+    ) -> bool;
+
+    fn emitted(&self, _r_in: &Ray, _rec: &HitRecord) -> Color {
+        Color::zero()
+    }
+
+    // Solid angle density with which `scatter` picks the direction of `scattered`.
+    fn pdf(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> f64 {
+        0.0
+    }
+
+    // Fraction of light arriving along `scattered` that leaves back along `r_in`, including the
+    // cosine with the surface normal for surfaces; media have no such factor.
+    fn evaluate(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> Color {
+        Color::zero()
+    }
+
+    // Whether this hit falls on a masked-out part of the surface and should be skipped.
+    fn is_cut_out(&self, _rec: &HitRecord) -> bool {
+        false
+    }
+
+    // Whether scattering happens at a surface rather than inside a medium, which decides whether
+    // the cosine with the normal enters into densities per unit area.
+    fn is_surface(&self) -> bool {
+        true
+    }
+}
 
 #[derive(Copy, Clone, Debug)]
-pub enum Material {
-    Lambertian(Color),
-    Metal(Color, f64),
-    Dielectric(f64),
+pub struct Lambertian {
+    albedo: Color,
+}
+
+impl Lambertian {
+    pub fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
 }
 
-impl Material {
-    pub fn scatter(
+impl Material for Lambertian {
+    fn scatter(
+        &self,
+        _r_in: &Ray,
+        rec: &HitRecord,
+        attenuation: &mut Color,
+        scattered: &mut Ray,
+    ) -> bool {
+        *scattered = Ray::new(rec.point, CosinePdf::new(rec.normal).generate());
+        *attenuation = self.albedo;
+        true
+    }
+
+    fn pdf(&self, _r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        CosinePdf::new(rec.normal).value(scattered.direction())
+    }
+
+    fn evaluate(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        self.albedo * self.pdf(r_in, rec, scattered)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Metal {
+    albedo: Color,
+    fuzz: f64,
+}
+
+impl Metal {
+    pub fn new(albedo: Color, fuzz: f64) -> Self {
+        Self { albedo, fuzz }
+    }
+}
+
+impl Material for Metal {
+    fn scatter(
         &self,
         r_in: &Ray,
         rec: &HitRecord,
         attenuation: &mut Color,
         scattered: &mut Ray,
     ) -> bool {
-        match self {
-            Material::Lambertian(albedo) => {
-                let mut scatter_direction = rec.normal + Vec3::random_unit_vector();
+        let reflected = r_in.direction().unit_vector().reflect(rec.normal);
+        *scattered = Ray::new(
+            rec.point,
+            reflected + Vec3::random_unit_vector() * self.fuzz,
+        );
+        *attenuation = self.albedo;
+        scattered.direction().dot(rec.normal) > 0.0
+    }
 
-                if scatter_direction.near_zero() {
-                    scatter_direction = rec.normal;
-                }
+    // Scattered directions point at a uniformly chosen spot on a sphere of radius `fuzz` around
+    // the mirror direction, so the density sums the sphere's area density over both places the
+    // direction crosses it, converted to solid angle.
+    fn pdf(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        if self.fuzz <= 0.0 {
+            return 0.0;
+        }
 
-                *scattered = Ray::new(rec.point, scatter_direction);
-                *attenuation = *albedo;
-                true
-            }
-            Material::Metal(albedo, fuzz) => {
-                let reflected = r_in.direction().unit_vector().reflect(rec.normal);
-                *scattered = Ray::new(rec.point, reflected + Vec3::random_unit_vector() * *fuzz);
-                *attenuation = *albedo;
-                scattered.direction().dot(rec.normal) > 0.0
-            }
-            Material::Dielectric(ir) => {
-                *attenuation = Color::new(1.0, 1.0, 1.0);
-                let refraction_ratio = if rec.front_face { 1.0 / *ir } else { *ir };
-
-                let unit_direction = r_in.direction().unit_vector();
-                let cos_theta = (-unit_direction).dot(rec.normal).min(1.0);
-                let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
-
-                let cannot_refract = refraction_ratio * sin_theta > 1.0;
-                let direction = if cannot_refract
-                    || reflectance(cos_theta, refraction_ratio) > rand::random()
-                {
-                    unit_direction.reflect(rec.normal)
-                } else {
-                    unit_direction.refract(rec.normal, refraction_ratio)
-                };
-
-                *scattered = Ray::new(rec.point, direction);
-                true
+        let direction = scattered.direction().unit_vector();
+        if direction.dot(rec.normal) <= 0.0 {
+            return 0.0;
+        }
+
+        let reflected = r_in.direction().unit_vector().reflect(rec.normal);
+        let along = direction.dot(reflected);
+        let discriminant = along * along - (1.0 - self.fuzz * self.fuzz);
+        if discriminant <= 0.0 {
+            return 0.0;
+        }
+
+        let root = discriminant.sqrt();
+        let crossings: f64 = [along - root, along + root]
+            .iter()
+            .filter(|&&t| t > 0.0)
+            .map(|t| t * t)
+            .sum();
+
+        crossings / (4.0 * PI * self.fuzz * root)
+    }
+
+    fn evaluate(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        self.albedo * self.pdf(r_in, rec, scattered)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Dielectric {
+    refraction_index: f64,
+}
+
+impl Dielectric {
+    pub fn new(refraction_index: f64) -> Self {
+        Self { refraction_index }
+    }
+}
+
+impl Material for Dielectric {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        attenuation: &mut Color,
+        scattered: &mut Ray,
+    ) -> bool {
+        *attenuation = Color::new(1.0, 1.0, 1.0);
+        let refraction_ratio = if rec.front_face {
+            1.0 / self.refraction_index
+        } else {
+            self.refraction_index
+        };
+
+        let unit_direction = r_in.direction().unit_vector();
+        let cos_theta = (-unit_direction).dot(rec.normal).min(1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+        let cannot_refract = refraction_ratio * sin_theta > 1.0;
+        let direction =
+            if cannot_refract || reflectance(cos_theta, refraction_ratio) > random_double() {
+                unit_direction.reflect(rec.normal)
+            } else {
+                unit_direction.refract(rec.normal, refraction_ratio)
+            };
+
+        *scattered = Ray::new(rec.point, direction);
+        true
+    }
+}
+
+// Emits from the front of a surface and absorbs everything that arrives.
+#[derive(Copy, Clone, Debug)]
+pub struct DiffuseLight {
+    emit: Color,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        Self { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(
+        &self,
+        _r_in: &Ray,
+        _rec: &HitRecord,
+        _attenuation: &mut Color,
+        _scattered: &mut Ray,
+    ) -> bool {
+        false
+    }
+
+    fn emitted(&self, _r_in: &Ray, rec: &HitRecord) -> Color {
+        if rec.front_face {
+            self.emit
+        } else {
+            Color::zero()
+        }
+    }
+}
+
+// Scattering inside a participating medium, where the phase function takes the place of a BSDF.
+#[derive(Copy, Clone, Debug)]
+pub struct Medium {
+    albedo: Color,
+    phase_function: PhaseFunction,
+}
+
+impl Medium {
+    pub fn new(albedo: Color, phase_function: PhaseFunction) -> Self {
+        Self {
+            albedo,
+            phase_function,
+        }
+    }
+}
+
+impl Material for Medium {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        attenuation: &mut Color,
+        scattered: &mut Ray,
+    ) -> bool {
+        *scattered = Ray::new(rec.point, self.phase_function.sample(r_in.direction()));
+        *attenuation = self.albedo;
+        true
+    }
+
+    fn pdf(&self, r_in: &Ray, _rec: &HitRecord, scattered: &Ray) -> f64 {
+        self.phase_function
+            .pdf(r_in.direction(), scattered.direction())
+    }
+
+    fn evaluate(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        self.albedo * self.pdf(r_in, rec, scattered)
+    }
+
+    fn is_surface(&self) -> bool {
+        false
+    }
+}
+
+// Tangent-space normal map, with components stored in [0, 1] as in common image formats.
+#[derive(Clone, Debug)]
+pub struct NormalMapped {
+    base: Arc<dyn Material>,
+    normal_map: Arc<dyn Texture>,
+}
+
+impl NormalMapped {
+    pub fn new(base: Arc<dyn Material>, normal_map: Arc<dyn Texture>) -> Self {
+        Self { base, normal_map }
+    }
+
+    fn shading_record(&self, r_in: &Ray, rec: &HitRecord) -> HitRecord {
+        let (tangent, bitangent, normal) = tangent_frame(rec);
+        let texel = self.normal_map.value(rec.u, rec.v, rec.point) * 2.0 - 1.0;
+        let perturbed = tangent * texel.x() + bitangent * texel.y() + normal * texel.z();
+
+        with_shading_normal(perturbed, r_in, rec)
+    }
+}
+
+impl Material for NormalMapped {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        attenuation: &mut Color,
+        scattered: &mut Ray,
+    ) -> bool {
+        let shading = self.shading_record(r_in, rec);
+        self.base.scatter(r_in, &shading, attenuation, scattered)
+            && same_side(scattered, &shading, rec)
+    }
+
+    fn emitted(&self, r_in: &Ray, rec: &HitRecord) -> Color {
+        self.base.emitted(r_in, rec)
+    }
+
+    fn pdf(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        let shading = self.shading_record(r_in, rec);
+        if !same_side(scattered, &shading, rec) {
+            return 0.0;
+        }
+
+        self.base.pdf(r_in, &shading, scattered)
+    }
+
+    fn evaluate(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        let shading = self.shading_record(r_in, rec);
+        if !same_side(scattered, &shading, rec) {
+            return Color::zero();
+        }
+
+        self.base.evaluate(r_in, &shading, scattered)
+    }
+
+    fn is_cut_out(&self, rec: &HitRecord) -> bool {
+        self.base.is_cut_out(rec)
+    }
+}
+
+// Grayscale height map and the strength of the bumps it produces.
+#[derive(Clone, Debug)]
+pub struct BumpMapped {
+    base: Arc<dyn Material>,
+    height_map: Arc<dyn Texture>,
+    strength: f64,
+}
+
+impl BumpMapped {
+    pub fn new(base: Arc<dyn Material>, height_map: Arc<dyn Texture>, strength: f64) -> Self {
+        Self {
+            base,
+            height_map,
+            strength,
+        }
+    }
+
+    fn shading_record(&self, r_in: &Ray, rec: &HitRecord) -> HitRecord {
+        const DELTA: f64 = 1e-3;
+
+        // Steps in texture space are matched by steps of the same world length across the
+        // surface, and slopes are per world unit, so bumps keep their strength however the
+        // surface is parameterized.
+        let (tangent, bitangent, normal) = tangent_frame(rec);
+        let (scale_u, scale_v) = rec.uv_scale;
+        let height = |du: f64, dv: f64| {
+            let p = rec.point + tangent * (du * scale_u) + bitangent * (dv * scale_v);
+            self.height_map.value(rec.u + du, rec.v + dv, p).x()
+        };
+        let slope = |rise: f64, scale: f64| {
+            if scale > 1e-12 {
+                rise / (DELTA * scale)
+            } else {
+                0.0
             }
+        };
+        let base_height = height(0.0, 0.0);
+        let slope_u = slope(height(DELTA, 0.0) - base_height, scale_u);
+        let slope_v = slope(height(0.0, DELTA) - base_height, scale_v);
+        let perturbed = normal - (tangent * slope_u + bitangent * slope_v) * self.strength;
+
+        with_shading_normal(perturbed, r_in, rec)
+    }
+}
+
+impl Material for BumpMapped {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        attenuation: &mut Color,
+        scattered: &mut Ray,
+    ) -> bool {
+        let shading = self.shading_record(r_in, rec);
+        self.base.scatter(r_in, &shading, attenuation, scattered)
+            && same_side(scattered, &shading, rec)
+    }
+
+    fn emitted(&self, r_in: &Ray, rec: &HitRecord) -> Color {
+        self.base.emitted(r_in, rec)
+    }
+
+    fn pdf(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        let shading = self.shading_record(r_in, rec);
+        if !same_side(scattered, &shading, rec) {
+            return 0.0;
+        }
+
+        self.base.pdf(r_in, &shading, scattered)
+    }
+
+    fn evaluate(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        let shading = self.shading_record(r_in, rec);
+        if !same_side(scattered, &shading, rec) {
+            return Color::zero();
         }
+
+        self.base.evaluate(r_in, &shading, scattered)
+    }
+
+    fn is_cut_out(&self, rec: &HitRecord) -> bool {
+        self.base.is_cut_out(rec)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum AlphaMode {
+    // Opaque where the opacity is at least the cutoff.
+    Threshold(f64),
+    // Opaque with probability equal to the opacity, which keeps soft edges unbiased.
+    Stochastic,
+}
+
+// Cutout whose opacity is the first channel of a texture; rays pass through wherever the mask
+// rejects the hit.
+#[derive(Clone, Debug)]
+pub struct Masked {
+    base: Arc<dyn Material>,
+    opacity: Arc<dyn Texture>,
+    mode: AlphaMode,
+}
+
+impl Masked {
+    pub fn new(base: Arc<dyn Material>, opacity: Arc<dyn Texture>, mode: AlphaMode) -> Self {
+        Self {
+            base,
+            opacity,
+            mode,
+        }
+    }
+}
+
+impl Material for Masked {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        attenuation: &mut Color,
+        scattered: &mut Ray,
+    ) -> bool {
+        self.base.scatter(r_in, rec, attenuation, scattered)
+    }
+
+    fn emitted(&self, r_in: &Ray, rec: &HitRecord) -> Color {
+        self.base.emitted(r_in, rec)
+    }
+
+    fn pdf(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        self.base.pdf(r_in, rec, scattered)
     }
+
+    fn evaluate(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        self.base.evaluate(r_in, rec, scattered)
+    }
+
+    fn is_cut_out(&self, rec: &HitRecord) -> bool {
+        let alpha = self.opacity.value(rec.u, rec.v, rec.point).x();
+        let opaque = match self.mode {
+            AlphaMode::Threshold(cutoff) => alpha >= cutoff,
+            AlphaMode::Stochastic => random_double() < alpha,
+        };
+
+        !opaque || self.base.is_cut_out(rec)
+    }
+}
+
+// Orthonormal tangent, bitangent and outward normal at a hit. Surfaces without a
+// parameterization get an arbitrary tangent.
+pub(crate) fn tangent_frame(rec: &HitRecord) -> (Vec3, Vec3, Vec3) {
+    let normal = if rec.front_face {
+        rec.normal
+    } else {
+        -rec.normal
+    };
+
+    let tangent = rec.tangent - normal * normal.dot(rec.tangent);
+    let tangent = if tangent.length_squared() > 1e-12 {
+        tangent.unit_vector()
+    } else {
+        Onb::new(normal).u()
+    };
+
+    (tangent, normal.cross(tangent), normal)
+}
+
+// The hit as if the surface faced along `outward_normal`, bent towards the viewer if the map
+// tilts it away.
+fn with_shading_normal(outward_normal: Vec3, r_in: &Ray, rec: &HitRecord) -> HitRecord {
+    const MIN_COSINE: f64 = 0.01;
+
+    let mut normal = if rec.front_face {
+        outward_normal.unit_vector()
+    } else {
+        -outward_normal.unit_vector()
+    };
+
+    let incoming = -r_in.direction().unit_vector();
+    let cosine = incoming.dot(normal);
+    if cosine.is_nan() {
+        normal = rec.normal;
+    } else if cosine < MIN_COSINE {
+        normal = (normal + incoming * (MIN_COSINE - cosine)).unit_vector();
+    }
+
+    HitRecord {
+        normal,
+        ..rec.clone()
+    }
+}
+
+// Rays that land on opposite sides of the shading and geometric surfaces are absorbed rather
+// than leaking through.
+fn same_side(scattered: &Ray, shading: &HitRecord, rec: &HitRecord) -> bool {
+    let direction = scattered.direction();
+    (direction.dot(shading.normal) > 0.0) == (direction.dot(rec.normal) > 0.0)
 }
 
 fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
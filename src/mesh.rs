@@ -0,0 +1,447 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::triangle::intersect_triangle;
+use crate::vec3::{Point3, Vec3};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+// A polygonal control mesh, as loaded from a modeling tool, before it is subdivided or
+// triangulated for rendering.
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    positions: Vec<Point3>,
+    uvs: Vec<(f64, f64)>,
+    faces: Vec<Vec<usize>>,
+    creases: HashSet<(usize, usize)>,
+}
+
+impl Mesh {
+    pub fn new(positions: Vec<Point3>, faces: Vec<Vec<usize>>) -> Self {
+        if let Some(problem) = face_problem(positions.len(), &faces) {
+            panic!("invalid control mesh: {problem}");
+        }
+
+        Self {
+            positions,
+            uvs: Vec::new(),
+            faces,
+            creases: HashSet::new(),
+        }
+    }
+
+    // Reads vertex positions, texture coordinates and polygonal faces from a Wavefront OBJ
+    // file. Texture coordinates are kept per vertex, so seams take the first one referenced.
+    pub fn from_obj(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let invalid = |line: usize| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("malformed OBJ line {line}"),
+            )
+        };
+
+        let mut positions = Vec::new();
+        let mut texture_coordinates = Vec::new();
+        let mut face_corners = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let mut tokens = line.split_whitespace();
+            let numbers = |tokens: std::str::SplitWhitespace| {
+                tokens
+                    .map(|token| token.parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| invalid(number + 1))
+            };
+
+            match tokens.next() {
+                Some("v") => {
+                    let values = numbers(tokens)?;
+                    if values.len() < 3 {
+                        return Err(invalid(number + 1));
+                    }
+                    positions.push(Point3::new(values[0], values[1], values[2]));
+                }
+                Some("vt") => {
+                    let values = numbers(tokens)?;
+                    if values.len() < 2 {
+                        return Err(invalid(number + 1));
+                    }
+                    texture_coordinates.push((values[0], values[1]));
+                }
+                Some("f") => {
+                    let resolve = |index: &str, count: usize| -> io::Result<usize> {
+                        let index: i64 = index.parse().map_err(|_| invalid(number + 1))?;
+                        let resolved = if index < 0 {
+                            count as i64 + index
+                        } else {
+                            index - 1
+                        };
+                        if resolved < 0 || resolved >= count as i64 {
+                            return Err(invalid(number + 1));
+                        }
+                        Ok(resolved as usize)
+                    };
+
+                    let mut corners = Vec::new();
+                    for corner in tokens {
+                        let mut parts = corner.split('/');
+                        let position = resolve(parts.next().unwrap_or(""), positions.len())?;
+                        let uv = match parts.next() {
+                            Some(index) if !index.is_empty() => {
+                                Some(resolve(index, texture_coordinates.len())?)
+                            }
+                            _ => None,
+                        };
+                        corners.push((position, uv));
+                    }
+                    if corners.len() < 3 {
+                        return Err(invalid(number + 1));
+                    }
+                    face_corners.push(corners);
+                }
+                _ => {}
+            }
+        }
+
+        let mut uvs = Vec::new();
+        if !texture_coordinates.is_empty() {
+            let mut assigned = vec![None; positions.len()];
+            for &(position, uv) in face_corners.iter().flatten() {
+                if let Some(uv) = uv {
+                    assigned[position].get_or_insert(texture_coordinates[uv]);
+                }
+            }
+            uvs = assigned
+                .into_iter()
+                .map(|uv| uv.unwrap_or((0.0, 0.0)))
+                .collect();
+        }
+
+        let faces: Vec<Vec<usize>> = face_corners
+            .into_iter()
+            .map(|corners| corners.into_iter().map(|(position, _)| position).collect())
+            .collect();
+        if let Some(problem) = face_problem(positions.len(), &faces) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, problem));
+        }
+
+        Ok(Self {
+            uvs,
+            ..Self::new(positions, faces)
+        })
+    }
+
+    pub fn positions(&self) -> &[Point3] {
+        &self.positions
+    }
+
+    pub fn uvs(&self) -> &[(f64, f64)] {
+        &self.uvs
+    }
+
+    pub fn faces(&self) -> &[Vec<usize>] {
+        &self.faces
+    }
+
+    pub fn set_uvs(&mut self, uvs: Vec<(f64, f64)>) {
+        assert_eq!(uvs.len(), self.positions.len());
+        self.uvs = uvs;
+    }
+
+    // Marks the edge between two vertices as sharp, so subdivision keeps it as a crease.
+    pub fn add_crease(&mut self, a: usize, b: usize) {
+        self.creases.insert(edge_key(a, b));
+    }
+
+    pub fn is_crease(&self, a: usize, b: usize) -> bool {
+        self.creases.contains(&edge_key(a, b))
+    }
+
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        self.faces
+            .iter()
+            .flat_map(|face| (1..face.len() - 1).map(move |i| [face[0], face[i], face[i + 1]]))
+            .collect()
+    }
+
+    // Triangulates the polygons and averages face normals onto the vertices for smooth shading.
+    pub fn to_triangle_mesh(&self, material: Arc<dyn Material>) -> TriangleMesh {
+        let triangles = self.triangles();
+        let normals = vertex_normals(&self.positions, &triangles);
+        TriangleMesh::new(
+            self.positions.clone(),
+            normals,
+            self.uvs.clone(),
+            triangles,
+            material,
+        )
+    }
+}
+
+// Why `faces` can't be subdivided over `vertex_count` vertices, if they can't. Edges shared by
+// more than two faces are allowed, and subdivide as creases.
+fn face_problem(vertex_count: usize, faces: &[Vec<usize>]) -> Option<&'static str> {
+    for face in faces {
+        if face.len() < 3 {
+            return Some("face has fewer than three vertices");
+        }
+        if face.iter().any(|&vertex| vertex >= vertex_count) {
+            return Some("face refers to a missing vertex");
+        }
+
+        let mut seen = HashSet::new();
+        if !face.iter().all(|&vertex| seen.insert(vertex)) {
+            return Some("face repeats a vertex");
+        }
+    }
+
+    None
+}
+
+pub(crate) fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+// Area-weighted average of the normals of the triangles around each vertex.
+pub(crate) fn vertex_normals(positions: &[Point3], triangles: &[[usize; 3]]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::zero(); positions.len()];
+    for &[a, b, c] in triangles {
+        let face_normal = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+        for vertex in [a, b, c] {
+            normals[vertex] += face_normal;
+        }
+    }
+
+    normals
+        .into_iter()
+        .map(|normal| {
+            if normal.near_zero() {
+                normal
+            } else {
+                normal.unit_vector()
+            }
+        })
+        .collect()
+}
+
+// Triangles are kept in a hierarchy of bounding boxes, so each ray only tests the few whose boxes
+// it passes through. Subdivided and displaced meshes easily reach hundreds of thousands.
+pub struct TriangleMesh {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    triangles: Vec<[usize; 3]>,
+    nodes: Vec<MeshNode>,
+    material: Arc<dyn Material>,
+    bounding_box: Aabb,
+}
+
+// A box in the hierarchy. Leaves cover `count` triangles from `start`; inner nodes have no
+// triangles of their own, and are followed by their first child, with the second at `start`.
+#[derive(Copy, Clone, Debug)]
+struct MeshNode {
+    bounds: Aabb,
+    start: usize,
+    count: usize,
+}
+
+const LEAF_TRIANGLES: usize = 4;
+// Enough for the hierarchy's depth, since every split halves the triangles.
+const MAX_NODE_DEPTH: usize = 64;
+
+impl TriangleMesh {
+    // Reorders the triangles as it builds their hierarchy.
+    pub fn new(
+        positions: Vec<Point3>,
+        normals: Vec<Vec3>,
+        uvs: Vec<(f64, f64)>,
+        mut triangles: Vec<[usize; 3]>,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let bounding_box = positions.iter().fold(Aabb::empty(), |bounds, &p| {
+            Aabb::surrounding(bounds, Aabb::from_points(p, p))
+        });
+
+        let mut nodes = Vec::new();
+        if !triangles.is_empty() {
+            build_nodes(&positions, &mut triangles, 0, &mut nodes);
+        }
+
+        Self {
+            positions,
+            normals,
+            uvs,
+            triangles,
+            nodes,
+            material,
+            bounding_box,
+        }
+    }
+
+    pub fn positions(&self) -> &[Point3] {
+        &self.positions
+    }
+
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    // Rates of change of position with u and with v across a triangle, taken from its texture
+    // coordinates when the mesh has them and from the barycentric parameterization used in
+    // their place otherwise.
+    fn derivatives(&self, index: usize) -> (Vec3, Vec3) {
+        let [a, b, c] = self.triangles[index];
+        let edge1 = self.positions[b] - self.positions[a];
+        let edge2 = self.positions[c] - self.positions[a];
+        if self.uvs.is_empty() {
+            return (edge1, edge2);
+        }
+
+        let (du1, dv1) = (self.uvs[b].0 - self.uvs[a].0, self.uvs[b].1 - self.uvs[a].1);
+        let (du2, dv2) = (self.uvs[c].0 - self.uvs[a].0, self.uvs[c].1 - self.uvs[a].1);
+        let determinant = du1 * dv2 - du2 * dv1;
+        if determinant.abs() < 1e-12 {
+            return (edge1, edge2);
+        }
+
+        (
+            (edge1 * dv2 - edge2 * dv1) / determinant,
+            (edge2 * du1 - edge1 * du2) / determinant,
+        )
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        if self.bounding_box.hit(ray, ray_t).is_none() {
+            return false;
+        }
+
+        let mut closest: Option<(usize, f64, f64, f64)> = None;
+        let mut stack = [0; MAX_NODE_DEPTH + 1];
+        let mut stack_len = usize::from(!self.nodes.is_empty());
+        while stack_len > 0 {
+            stack_len -= 1;
+            let index = stack[stack_len];
+            let node = self.nodes[index];
+            let closest_so_far = closest.map_or(ray_t.max(), |(_, t, _, _)| t);
+            if node
+                .bounds
+                .hit(ray, Interval::new(ray_t.min(), closest_so_far))
+                .is_none()
+            {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack[stack_len] = node.start;
+                stack[stack_len + 1] = index + 1;
+                stack_len += 2;
+                continue;
+            }
+
+            for triangle in node.start..node.start + node.count {
+                let [a, b, c] = self.triangles[triangle];
+                let closest_so_far = closest.map_or(ray_t.max(), |(_, t, _, _)| t);
+                if let Some((t, beta, gamma)) = intersect_triangle(
+                    ray,
+                    self.positions[a],
+                    self.positions[b],
+                    self.positions[c],
+                    Interval::new(ray_t.min(), closest_so_far),
+                ) {
+                    closest = Some((triangle, t, beta, gamma));
+                }
+            }
+        }
+
+        let Some((index, t, beta, gamma)) = closest else {
+            return false;
+        };
+        let [a, b, c] = self.triangles[index];
+        let alpha = 1.0 - beta - gamma;
+
+        let geometric_normal =
+            (self.positions[b] - self.positions[a]).cross(self.positions[c] - self.positions[a]);
+        let mut normal = self.normals[a] * alpha + self.normals[b] * beta + self.normals[c] * gamma;
+        if normal.near_zero() {
+            normal = geometric_normal;
+        }
+
+        record.t = t;
+        record.point = ray.at(t);
+        record.material = self.material.clone();
+        record.set_face_normal(ray, normal.unit_vector());
+        let (dpdu, dpdv) = self.derivatives(index);
+        record.tangent = dpdu.unit_vector();
+        (record.u, record.v) = if self.uvs.is_empty() {
+            (beta, gamma)
+        } else {
+            (
+                self.uvs[a].0 * alpha + self.uvs[b].0 * beta + self.uvs[c].0 * gamma,
+                self.uvs[a].1 * alpha + self.uvs[b].1 * beta + self.uvs[c].1 * gamma,
+            )
+        };
+        record.uv_scale = (dpdu.length(), dpdv.length());
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
+
+// Appends the hierarchy over `triangles`, which start at `start` in the mesh's list, splitting
+// them at the median of their centers along the longest axis of their bounds.
+fn build_nodes(
+    positions: &[Point3],
+    triangles: &mut [[usize; 3]],
+    start: usize,
+    nodes: &mut Vec<MeshNode>,
+) {
+    let bounds = triangles.iter().fold(Aabb::empty(), |bounds, &[a, b, c]| {
+        let triangle = Aabb::surrounding(
+            Aabb::from_points(positions[a], positions[b]),
+            Aabb::from_points(positions[c], positions[c]),
+        );
+        Aabb::surrounding(bounds, triangle)
+    });
+
+    let index = nodes.len();
+    nodes.push(MeshNode {
+        bounds,
+        start,
+        count: triangles.len(),
+    });
+    if triangles.len() <= LEAF_TRIANGLES {
+        return;
+    }
+
+    let axis = (0..3)
+        .max_by(|&a, &b| bounds.axis(a).size().total_cmp(&bounds.axis(b).size()))
+        .unwrap_or(0);
+    let center = |&[a, b, c]: &[usize; 3]| {
+        let sum = positions[a] + positions[b] + positions[c];
+        match axis {
+            1 => sum.y(),
+            2 => sum.z(),
+            _ => sum.x(),
+        }
+    };
+
+    let middle = triangles.len() / 2;
+    triangles.select_nth_unstable_by(middle, |a, b| center(a).total_cmp(&center(b)));
+    let (first, second) = triangles.split_at_mut(middle);
+
+    build_nodes(positions, first, start, nodes);
+    nodes[index].start = nodes.len();
+    nodes[index].count = 0;
+    build_nodes(positions, second, start + middle, nodes);
+}
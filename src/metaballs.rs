@@ -0,0 +1,242 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::cell::RefCell;
+use std::sync::Arc;
+
+const MAX_DEPTH: u32 = 32;
+const TOLERANCE: f64 = 1e-7;
+
+// A blob contributing weight * (1 - r^2 / radius^2)^3 to the field within `radius` of its center.
+#[derive(Copy, Clone, Debug)]
+pub struct Metaball {
+    center: Point3,
+    radius: f64,
+    weight: f64,
+}
+
+impl Metaball {
+    pub fn new(center: Point3, radius: f64, weight: f64) -> Self {
+        Self {
+            center,
+            radius,
+            weight,
+        }
+    }
+
+    fn gradient(&self, p: Point3) -> Vec3 {
+        let offset = p - self.center;
+        let radius_squared = self.radius * self.radius;
+        let falloff = 1.0 - offset.length_squared() / radius_squared;
+        if falloff <= 0.0 {
+            Vec3::zero()
+        } else {
+            offset * (-6.0 * self.weight * falloff * falloff / radius_squared)
+        }
+    }
+}
+
+// One ball's falloff along a ray, alpha t^2 + beta t + gamma, within its support.
+#[derive(Copy, Clone, Debug)]
+struct Term {
+    alpha: f64,
+    beta: f64,
+    gamma: f64,
+    weight: f64,
+}
+
+impl Term {
+    // The falloff is a downward parabola, so its range over `t` is bounded by the end points
+    // and, when it lies inside, the vertex.
+    fn field_bounds(&self, t: Interval) -> Interval {
+        let at = |t: f64| (self.alpha * t + self.beta) * t + self.gamma;
+
+        let (start, end) = (at(t.min()), at(t.max()));
+        let mut max = start.max(end);
+        let vertex = -self.beta / (2.0 * self.alpha);
+        if t.contains(vertex) {
+            max = max.max(at(vertex));
+        }
+        let min = start.min(end).max(0.0);
+        let max = max.max(0.0);
+
+        // Negative weights carve into the field, flipping the bounds.
+        let (low, high) = (self.weight * min * min * min, self.weight * max * max * max);
+        Interval::new(low.min(high), low.max(high))
+    }
+}
+
+// Lists built for each ray, kept between hits so that tracing doesn't allocate.
+#[derive(Default)]
+struct Scratch {
+    supports: Vec<(Interval, Term)>,
+    breakpoints: Vec<f64>,
+    terms: Vec<Term>,
+}
+
+thread_local! {
+    static SCRATCH: RefCell<Scratch> = RefCell::new(Scratch::default());
+}
+
+// An isosurface of the summed field of several metaballs, which merge smoothly where their
+// regions of influence overlap.
+pub struct Metaballs {
+    balls: Vec<Metaball>,
+    threshold: f64,
+    material: Arc<dyn Material>,
+    bounding_box: Aabb,
+}
+
+impl Metaballs {
+    pub fn new(balls: Vec<Metaball>, threshold: f64, material: Arc<dyn Material>) -> Self {
+        let bounding_box = balls.iter().fold(Aabb::empty(), |bounds, ball| {
+            let extent = Vec3::new(ball.radius, ball.radius, ball.radius);
+            Aabb::surrounding(
+                bounds,
+                Aabb::from_points(ball.center - extent, ball.center + extent),
+            )
+        });
+
+        Self {
+            balls,
+            threshold,
+            material,
+            bounding_box,
+        }
+    }
+
+    fn gradient(&self, p: Point3) -> Vec3 {
+        self.balls
+            .iter()
+            .fold(Vec3::zero(), |sum, ball| sum + ball.gradient(p))
+    }
+
+    // Bisects `t` depth first, nearer half first, discarding halves whose field bounds cannot
+    // reach the threshold. The first crossing found is therefore the nearest.
+    fn find_root(&self, terms: &[Term], t: Interval, tolerance: f64) -> Option<f64> {
+        // Each level leaves at most its farther half waiting.
+        let mut stack = [(t, 0); MAX_DEPTH as usize + 1];
+        let mut stack_len = 1;
+        while stack_len > 0 {
+            stack_len -= 1;
+            let (t, depth) = stack[stack_len];
+
+            let bounds = terms.iter().fold(Interval::new(0.0, 0.0), |sum, term| {
+                let term_bounds = term.field_bounds(t);
+                Interval::new(sum.min() + term_bounds.min(), sum.max() + term_bounds.max())
+            });
+            if !bounds.contains(self.threshold) {
+                continue;
+            }
+
+            let midpoint = 0.5 * (t.min() + t.max());
+            if t.size() <= tolerance || depth == MAX_DEPTH {
+                return Some(midpoint);
+            }
+
+            stack[stack_len] = (Interval::new(midpoint, t.max()), depth + 1);
+            stack[stack_len + 1] = (Interval::new(t.min(), midpoint), depth + 1);
+            stack_len += 2;
+        }
+
+        None
+    }
+
+    // Nearest crossing of the threshold along the ray. Each ball's influence along the ray is a
+    // cubed quadratic, so between consecutive support boundaries the field is a fixed polynomial
+    // that can be bounded tightly on any interval.
+    fn nearest_root(&self, ray: Ray, ray_t: Interval, scratch: &mut Scratch) -> Option<f64> {
+        let direction_squared = ray.direction().length_squared();
+        let Scratch {
+            supports,
+            breakpoints,
+            terms,
+        } = scratch;
+
+        supports.clear();
+        for ball in &self.balls {
+            let oc = ray.origin() - ball.center;
+            let radius_squared = ball.radius * ball.radius;
+            let half_b = oc.dot(ray.direction());
+            let discriminant =
+                half_b * half_b - direction_squared * (oc.length_squared() - radius_squared);
+            if discriminant <= 0.0 {
+                continue;
+            }
+
+            let sqrtd = discriminant.sqrt();
+            let support = Interval::new(
+                (-half_b - sqrtd) / direction_squared,
+                (-half_b + sqrtd) / direction_squared,
+            );
+            let term = Term {
+                alpha: -direction_squared / radius_squared,
+                beta: -2.0 * half_b / radius_squared,
+                gamma: 1.0 - oc.length_squared() / radius_squared,
+                weight: ball.weight,
+            };
+            supports.push((support, term));
+        }
+
+        breakpoints.clear();
+        breakpoints.extend(
+            supports
+                .iter()
+                .flat_map(|(support, _)| [support.min(), support.max()])
+                .filter(|&t| ray_t.surrounds(t))
+                .chain([ray_t.min(), ray_t.max()])
+                .filter(|t| t.is_finite()),
+        );
+        breakpoints.sort_by(f64::total_cmp);
+
+        let tolerance = TOLERANCE / direction_squared.sqrt();
+        for piece in breakpoints.windows(2) {
+            let piece = Interval::new(piece[0], piece[1]);
+            let midpoint = 0.5 * (piece.min() + piece.max());
+            terms.clear();
+            terms.extend(
+                supports
+                    .iter()
+                    .filter(|(support, _)| support.contains(midpoint))
+                    .map(|&(_, term)| term),
+            );
+            if terms.is_empty() {
+                continue;
+            }
+
+            if let Some(root) = self.find_root(terms, piece, tolerance) {
+                return Some(root);
+            }
+        }
+
+        None
+    }
+}
+
+impl Hittable for Metaballs {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let found =
+            SCRATCH.with(|scratch| self.nearest_root(ray, ray_t, &mut scratch.borrow_mut()));
+        let Some(root) = found.filter(|&root| ray_t.surrounds(root)) else {
+            return false;
+        };
+
+        record.t = root;
+        record.point = ray.at(root);
+        record.material = self.material.clone();
+        record.set_face_normal(ray, -self.gradient(record.point).unit_vector());
+        record.tangent = Vec3::zero();
+        record.u = 0.0;
+        record.v = 0.0;
+        record.uv_scale = (1.0, 1.0);
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
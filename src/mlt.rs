@@ -0,0 +1,149 @@
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::film::Film;
+use crate::integrator::{Integrator, PathTracer, Radiance};
+use crate::sampler::{with_sampler, PrimarySampleSpace};
+use crate::scene::Scene;
+use crate::utility::random_double;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Primary sample space Metropolis light transport. Every path the path tracer could follow,
+// including the pixel it passes through, is a function of the random numbers it draws; Markov
+// chains over those numbers visit paths in proportion to their brightness, so once a chain finds
+// a hard-to-reach light path it explores the paths near it. Chains start from bootstrap paths,
+// whose average brightness also scales the result to match the path tracer's.
+#[derive(Copy, Clone, Debug)]
+pub struct MetropolisLightTransport {
+    path_tracer: PathTracer,
+    bootstrap_samples: u64,
+    chains: u64,
+    sigma: f64,
+    large_step_probability: f64,
+}
+
+impl MetropolisLightTransport {
+    pub fn new(
+        max_depth: u32,
+        bootstrap_samples: u64,
+        chains: u64,
+        sigma: f64,
+        large_step_probability: f64,
+    ) -> Self {
+        Self {
+            path_tracer: PathTracer::new(max_depth),
+            bootstrap_samples,
+            chains,
+            sigma,
+            large_step_probability,
+        }
+    }
+
+    // Picks a pixel and traces a path through it, with every random number taken from `sampler`.
+    fn trace(
+        &self,
+        sampler: &Rc<RefCell<PrimarySampleSpace>>,
+        camera: &Camera,
+        scene: &Scene,
+    ) -> (u32, u32, Color) {
+        with_sampler(sampler.clone(), || {
+            let width = camera.image_width();
+            let height = camera.image_height();
+            let i = ((random_double() * width as f64) as u32).min(width - 1);
+            let j = ((random_double() * height as f64) as u32).min(height - 1);
+            let ray = camera.get_ray(i, j);
+
+            let color = self.path_tracer.radiance(ray, scene);
+            (i, j, color)
+        })
+    }
+
+    fn sampler(&self, seed: u64) -> Rc<RefCell<PrimarySampleSpace>> {
+        Rc::new(RefCell::new(PrimarySampleSpace::new(
+            seed,
+            self.sigma,
+            self.large_step_probability,
+        )))
+    }
+}
+
+impl Integrator for MetropolisLightTransport {
+    // Takes the camera's samples per pixel as the number of mutations per pixel.
+    fn render(&self, camera: &Camera, scene: &Scene) -> Film {
+        let mutations_per_pixel = camera.samples_per_pixel();
+        let mut film = Film::new(
+            camera.image_width(),
+            camera.image_height(),
+            mutations_per_pixel,
+        );
+
+        eprintln!("Bootstrapping with {} paths.", self.bootstrap_samples);
+        let weights: Vec<f64> = (0..self.bootstrap_samples)
+            .map(|seed| {
+                let (_, _, color) = self.trace(&self.sampler(seed), camera, scene);
+                color.luminance().max(0.0)
+            })
+            .collect();
+
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 || self.chains == 0 {
+            return film;
+        }
+        let brightness = total / self.bootstrap_samples as f64;
+
+        let pixels = u64::from(camera.image_width()) * u64::from(camera.image_height());
+        let mutations = (pixels * u64::from(mutations_per_pixel)).div_ceil(self.chains);
+        // Each mutation records `brightness` in all, which the film then averages over the
+        // mutations per pixel it expects.
+        let scale = brightness * (pixels * u64::from(mutations_per_pixel)) as f64
+            / (mutations * self.chains) as f64;
+        for chain in 0..self.chains {
+            eprintln!("Chains remaining: {}", self.chains - chain);
+
+            // Chains start from bootstrap paths picked in proportion to their brightness.
+            let mut target = random_double() * total;
+            let seed = weights
+                .iter()
+                .position(|&weight| {
+                    target -= weight;
+                    target < 0.0
+                })
+                .unwrap_or(weights.len() - 1);
+
+            let sampler = self.sampler(seed as u64);
+            let mut current = self.trace(&sampler, camera, scene);
+            for _ in 0..mutations {
+                sampler.borrow_mut().start_iteration();
+                let proposed = self.trace(&sampler, camera, scene);
+
+                let current_luminance = current.2.luminance();
+                let proposed_luminance = proposed.2.luminance();
+                let accept = if current_luminance > 0.0 {
+                    (proposed_luminance / current_luminance).clamp(0.0, 1.0)
+                } else {
+                    1.0
+                };
+
+                // Both paths are recorded, weighted by their chances of being kept.
+                if proposed_luminance > 0.0 {
+                    let weight = accept * scale / proposed_luminance;
+                    film.add_sample(proposed.0, proposed.1, proposed.2 * weight);
+                }
+                if current_luminance > 0.0 {
+                    let weight = (1.0 - accept) * scale / current_luminance;
+                    film.add_sample(current.0, current.1, current.2 * weight);
+                }
+
+                if random_double() < accept {
+                    sampler.borrow_mut().accept();
+                    current = proposed;
+                } else {
+                    sampler.borrow_mut().reject();
+                }
+            }
+        }
+
+        eprintln!("Done.");
+        film
+    }
+}
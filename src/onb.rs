@@ -0,0 +1,39 @@
+use crate::vec3::Vec3;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Onb {
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Onb {
+    pub fn new(w: Vec3) -> Self {
+        let w = w.unit_vector();
+        let a = if w.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(a).unit_vector();
+        let u = w.cross(v);
+
+        Self { u, v, w }
+    }
+
+    pub fn u(self) -> Vec3 {
+        self.u
+    }
+
+    pub fn v(self) -> Vec3 {
+        self.v
+    }
+
+    pub fn w(self) -> Vec3 {
+        self.w
+    }
+
+    pub fn local(self, a: f64, b: f64, c: f64) -> Vec3 {
+        self.u * a + self.v * b + self.w * c
+    }
+}
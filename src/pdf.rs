@@ -0,0 +1,54 @@
+use crate::onb::Onb;
+use crate::vec3::Vec3;
+use std::f64::consts::PI;
+
+// A distribution of directions that can be both sampled and evaluated, with densities measured
+// in solid angle.
+pub trait Pdf {
+    fn value(&self, direction: Vec3) -> f64;
+
+    fn generate(&self) -> Vec3;
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SpherePdf;
+
+impl SpherePdf {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Pdf for SpherePdf {
+    fn value(&self, _direction: Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn generate(&self) -> Vec3 {
+        Vec3::random_unit_vector()
+    }
+}
+
+// Cosine-weighted directions over the hemisphere around `w`.
+#[derive(Copy, Clone, Debug)]
+pub struct CosinePdf {
+    basis: Onb,
+}
+
+impl CosinePdf {
+    pub fn new(w: Vec3) -> Self {
+        Self { basis: Onb::new(w) }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: Vec3) -> f64 {
+        let cosine = direction.unit_vector().dot(self.basis.w());
+        cosine.max(0.0) / PI
+    }
+
+    fn generate(&self) -> Vec3 {
+        let d = Vec3::random_cosine_direction();
+        self.basis.local(d.x(), d.y(), d.z())
+    }
+}
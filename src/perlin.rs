@@ -0,0 +1,107 @@
+use crate::utility::random_int;
+use crate::vec3::{Point3, Vec3};
+
+const POINT_COUNT: usize = 256;
+
+#[derive(Clone, Debug)]
+pub struct Perlin {
+    random_vectors: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new() -> Self {
+        let random_vectors = (0..POINT_COUNT)
+            .map(|_| Vec3::random_range(-1.0, 1.0).unit_vector())
+            .collect();
+
+        Self {
+            random_vectors,
+            perm_x: generate_permutation(),
+            perm_y: generate_permutation(),
+            perm_z: generate_permutation(),
+        }
+    }
+
+    pub fn noise(&self, p: Point3) -> f64 {
+        let u = p.x() - p.x().floor();
+        let v = p.y() - p.y().floor();
+        let w = p.z() - p.z().floor();
+
+        let i = p.x().floor() as i64;
+        let j = p.y().floor() as i64;
+        let k = p.z().floor() as i64;
+
+        let mut c = [[[Vec3::zero(); 2]; 2]; 2];
+        for (di, plane) in c.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    *corner = self.random_vectors[self.perm_x[wrap(i + di as i64)]
+                        ^ self.perm_y[wrap(j + dj as i64)]
+                        ^ self.perm_z[wrap(k + dk as i64)]];
+                }
+            }
+        }
+
+        perlin_interpolate(&c, u, v, w)
+    }
+
+    pub fn turbulence(&self, p: Point3, depth: u32) -> f64 {
+        let mut accumulated = 0.0;
+        let mut temp_p = p;
+        let mut weight = 1.0;
+
+        for _ in 0..depth {
+            accumulated += weight * self.noise(temp_p);
+            weight *= 0.5;
+            temp_p = temp_p * 2.0;
+        }
+
+        accumulated.abs()
+    }
+}
+
+impl Default for Perlin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn wrap(i: i64) -> usize {
+    (i & (POINT_COUNT as i64 - 1)) as usize
+}
+
+fn generate_permutation() -> Vec<usize> {
+    let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+
+    for i in (1..POINT_COUNT).rev() {
+        let target = random_int(0, i as i32) as usize;
+        p.swap(i, target);
+    }
+
+    p
+}
+
+fn perlin_interpolate(c: &[[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+    let uu = u * u * (3.0 - 2.0 * u);
+    let vv = v * v * (3.0 - 2.0 * v);
+    let ww = w * w * (3.0 - 2.0 * w);
+
+    let mut accumulated = 0.0;
+    for (i, plane) in c.iter().enumerate() {
+        for (j, row) in plane.iter().enumerate() {
+            for (k, corner) in row.iter().enumerate() {
+                let (fi, fj, fk) = (i as f64, j as f64, k as f64);
+                let weight = Vec3::new(u - fi, v - fj, w - fk);
+                accumulated += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                    * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                    * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                    * corner.dot(weight);
+            }
+        }
+    }
+
+    accumulated
+}
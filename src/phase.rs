@@ -0,0 +1,84 @@
+use crate::onb::Onb;
+use crate::utility::random_double;
+use crate::vec3::Vec3;
+use std::f64::consts::PI;
+
+// Phase functions are parameterized by the cosine between the incident propagation
+// direction and the scattered direction, so positive asymmetry scatters forward.
+#[derive(Copy, Clone, Debug)]
+pub enum PhaseFunction {
+    Isotropic,
+    HenyeyGreenstein(f64),
+    Rayleigh,
+    DoubleHenyeyGreenstein {
+        forward: f64,
+        backward: f64,
+        weight: f64,
+    },
+}
+
+impl PhaseFunction {
+    pub fn pdf(&self, direction: Vec3, scattered: Vec3) -> f64 {
+        let cos_theta = direction.unit_vector().dot(scattered.unit_vector());
+
+        match *self {
+            PhaseFunction::Isotropic => 1.0 / (4.0 * PI),
+            PhaseFunction::HenyeyGreenstein(g) => henyey_greenstein(cos_theta, g),
+            PhaseFunction::Rayleigh => 3.0 / (16.0 * PI) * (1.0 + cos_theta * cos_theta),
+            PhaseFunction::DoubleHenyeyGreenstein {
+                forward,
+                backward,
+                weight,
+            } => {
+                weight * henyey_greenstein(cos_theta, forward)
+                    + (1.0 - weight) * henyey_greenstein(cos_theta, backward)
+            }
+        }
+    }
+
+    pub fn sample(&self, direction: Vec3) -> Vec3 {
+        let cos_theta = match *self {
+            PhaseFunction::Isotropic => 1.0 - 2.0 * random_double(),
+            PhaseFunction::HenyeyGreenstein(g) => sample_henyey_greenstein(g),
+            PhaseFunction::Rayleigh => sample_rayleigh(),
+            PhaseFunction::DoubleHenyeyGreenstein {
+                forward,
+                backward,
+                weight,
+            } => {
+                if random_double() < weight {
+                    sample_henyey_greenstein(forward)
+                } else {
+                    sample_henyey_greenstein(backward)
+                }
+            }
+        };
+
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * random_double();
+
+        Onb::new(direction).local(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+    }
+}
+
+fn henyey_greenstein(cos_theta: f64, g: f64) -> f64 {
+    let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+    (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+}
+
+fn sample_henyey_greenstein(g: f64) -> f64 {
+    let xi = random_double();
+    if g.abs() < 1e-3 {
+        return 1.0 - 2.0 * xi;
+    }
+
+    let term = (1.0 - g * g) / (1.0 - g + 2.0 * g * xi);
+    ((1.0 + g * g - term * term) / (2.0 * g)).clamp(-1.0, 1.0)
+}
+
+// Inverts the Rayleigh CDF, which reduces to the depressed cubic x^3 + 3x + 4 - 8xi = 0.
+fn sample_rayleigh() -> f64 {
+    let z = 4.0 * random_double() - 2.0;
+    let root = (z * z + 1.0).sqrt();
+    ((z + root).cbrt() + (z - root).cbrt()).clamp(-1.0, 1.0)
+}
@@ -0,0 +1,531 @@
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::film::Film;
+use crate::hittable::{HitRecord, Hittable};
+use crate::integrator::{
+    background, estimate_direct, render_pixels, survival_probability, Integrator,
+};
+use crate::interval::Interval;
+use crate::pdf::{CosinePdf, Pdf};
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::utility::random_double;
+use crate::vec3::{Point3, Vec3};
+use std::f64::consts::PI;
+
+// A packet of light left on a surface by a path traced from the lights.
+#[derive(Copy, Clone, Debug)]
+pub struct Photon {
+    pub position: Point3,
+    // Direction of travel when the photon arrived.
+    pub direction: Vec3,
+    pub power: Color,
+}
+
+// Photons arranged in a balanced kd-tree. The tree is stored implicitly: each slice's median
+// photon splits the rest, along the axis recorded for it.
+#[derive(Clone, Debug)]
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+    axes: Vec<usize>,
+}
+
+impl PhotonMap {
+    pub fn new(photons: Vec<Photon>) -> Self {
+        let mut photons = photons;
+        let mut axes = vec![0; photons.len()];
+        build(&mut photons, &mut axes);
+
+        Self { photons, axes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    // Calls `f` with every photon no further than `radius` from `center`.
+    pub fn for_each_within(&self, center: Point3, radius: f64, mut f: impl FnMut(&Photon)) {
+        visit(&self.photons, &self.axes, center, radius, &mut f);
+    }
+}
+
+fn coordinate(p: Point3, axis: usize) -> f64 {
+    match axis {
+        0 => p.x(),
+        1 => p.y(),
+        _ => p.z(),
+    }
+}
+
+fn build(photons: &mut [Photon], axes: &mut [usize]) {
+    if photons.is_empty() {
+        return;
+    }
+
+    // Split along the axis the photons spread furthest over.
+    let axis = (0..3)
+        .map(|axis| {
+            let (min, max) =
+                photons
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), photon| {
+                        let c = coordinate(photon.position, axis);
+                        (min.min(c), max.max(c))
+                    });
+            (axis, max - min)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(0, |(axis, _)| axis);
+
+    let mid = photons.len() / 2;
+    photons.select_nth_unstable_by(mid, |a, b| {
+        coordinate(a.position, axis).total_cmp(&coordinate(b.position, axis))
+    });
+    axes[mid] = axis;
+
+    let (left, rest) = photons.split_at_mut(mid);
+    let (left_axes, rest_axes) = axes.split_at_mut(mid);
+    build(left, left_axes);
+    build(&mut rest[1..], &mut rest_axes[1..]);
+}
+
+fn visit(
+    photons: &[Photon],
+    axes: &[usize],
+    center: Point3,
+    radius: f64,
+    f: &mut impl FnMut(&Photon),
+) {
+    if photons.is_empty() {
+        return;
+    }
+
+    let mid = photons.len() / 2;
+    let photon = &photons[mid];
+    if (photon.position - center).length_squared() <= radius * radius {
+        f(photon);
+    }
+
+    let delta = coordinate(center, axes[mid]) - coordinate(photon.position, axes[mid]);
+    let (near, far) = if delta < 0.0 {
+        (
+            (&photons[..mid], &axes[..mid]),
+            (&photons[mid + 1..], &axes[mid + 1..]),
+        )
+    } else {
+        (
+            (&photons[mid + 1..], &axes[mid + 1..]),
+            (&photons[..mid], &axes[..mid]),
+        )
+    };
+
+    visit(near.0, near.1, center, radius, f);
+    if delta.abs() <= radius {
+        visit(far.0, far.1, center, radius, f);
+    }
+}
+
+// Renders with a photon map built before any camera rays are traced. Camera rays pass through
+// specular bounces to the first diffuse surface, where direct light is sampled as usual and the
+// rest is estimated from the photons within `radius`. With a final gather, only caustics are read
+// from the photons there; other indirect light is found by one more bounce, reading all photons
+// where it lands. Photons only leave the sampled lights, so the background lights surfaces
+// directly but never indirectly.
+#[derive(Copy, Clone, Debug)]
+pub struct PhotonMapper {
+    photon_count: usize,
+    radius: f64,
+    max_depth: u32,
+    final_gather: bool,
+}
+
+impl PhotonMapper {
+    pub fn new(photon_count: usize, radius: f64, max_depth: u32, final_gather: bool) -> Self {
+        Self {
+            photon_count,
+            radius,
+            max_depth,
+            final_gather,
+        }
+    }
+
+    fn build_maps(&self, scene: &Scene) -> (PhotonMap, PhotonMap) {
+        let photons = trace_photons(scene, self.photon_count, self.max_depth);
+        eprintln!("Stored {} photons.", photons.len());
+
+        let select = |keep: &dyn Fn(Landing) -> bool| {
+            let kept = photons.iter().filter(|(_, landing)| keep(*landing));
+            PhotonMap::new(kept.map(|(photon, _)| *photon).collect())
+        };
+
+        if self.final_gather {
+            (
+                select(&|landing| landing == Landing::Caustic),
+                select(&|_| true),
+            )
+        } else {
+            (
+                PhotonMap::new(Vec::new()),
+                select(&|landing| landing != Landing::Direct),
+            )
+        }
+    }
+
+    fn estimate(&self, ray: Ray, scene: &Scene, caustics: &PhotonMap, global: &PhotonMap) -> Color {
+        let (mut color, visible) = trace_to_diffuse(ray, scene, self.max_depth, true);
+        let Some(visible) = visible else {
+            return color;
+        };
+
+        if !self.final_gather {
+            return color + visible.beta * density(global, &visible, self.radius);
+        }
+
+        color += visible.beta * density(caustics, &visible, self.radius);
+
+        // Light found directly by the gather ray was already sampled, so only the photons where
+        // it lands count.
+        let mut scattered = Ray::default();
+        let mut attenuation = Color::default();
+        let rec = &visible.rec;
+        if !rec
+            .material
+            .scatter(&visible.incoming, rec, &mut attenuation, &mut scattered)
+        {
+            return color;
+        }
+
+        let pdf = rec.material.pdf(&visible.incoming, rec, &scattered);
+        if pdf <= 0.0 {
+            return color;
+        }
+
+        let weight = rec.material.evaluate(&visible.incoming, rec, &scattered) / pdf;
+        if let (_, Some(gathered)) = trace_to_diffuse(scattered, scene, self.max_depth, false) {
+            color +=
+                visible.beta * weight * gathered.beta * density(global, &gathered, self.radius);
+        }
+
+        color
+    }
+}
+
+impl Integrator for PhotonMapper {
+    fn render(&self, camera: &Camera, scene: &Scene) -> Film {
+        let (caustics, global) = self.build_maps(scene);
+        render_pixels(camera, |ray, _| {
+            self.estimate(ray, scene, &caustics, &global)
+        })
+    }
+}
+
+// Stochastic progressive photon mapping. Each iteration traces one camera ray per pixel to a
+// diffuse surface and a fresh batch of photons, and every pixel shrinks its gathering radius as
+// photons accumulate, keeping a fraction `alpha` of the new ones. The result converges to the
+// right answer, where a fixed radius would stay blurred.
+#[derive(Copy, Clone, Debug)]
+pub struct ProgressivePhotonMapper {
+    iterations: u32,
+    photons_per_iteration: usize,
+    initial_radius: f64,
+    alpha: f64,
+    max_depth: u32,
+}
+
+impl ProgressivePhotonMapper {
+    pub fn new(
+        iterations: u32,
+        photons_per_iteration: usize,
+        initial_radius: f64,
+        alpha: f64,
+        max_depth: u32,
+    ) -> Self {
+        Self {
+            iterations,
+            photons_per_iteration,
+            initial_radius,
+            alpha,
+            max_depth,
+        }
+    }
+
+    // Estimates the light along the rays `generate` produces for each of `pixels` pixels.
+    fn estimate(
+        &self,
+        scene: &Scene,
+        pixels: usize,
+        generate: impl Fn(usize) -> Ray,
+    ) -> Vec<Color> {
+        let mut states = vec![
+            PixelState {
+                radius: self.initial_radius,
+                photons: 0.0,
+                flux: Color::zero(),
+                direct: Color::zero(),
+            };
+            pixels
+        ];
+
+        for iteration in 0..self.iterations {
+            eprintln!("Iterations remaining: {}", self.iterations - iteration);
+
+            let visible: Vec<_> = states
+                .iter_mut()
+                .enumerate()
+                .map(|(index, state)| {
+                    let (color, visible) =
+                        trace_to_diffuse(generate(index), scene, self.max_depth, true);
+                    state.direct += color;
+                    visible
+                })
+                .collect();
+
+            let photons = trace_photons(scene, self.photons_per_iteration, self.max_depth)
+                .into_iter()
+                .filter(|(_, landing)| *landing != Landing::Direct)
+                .map(|(photon, _)| photon)
+                .collect();
+            let map = PhotonMap::new(photons);
+
+            for (state, visible) in states.iter_mut().zip(visible) {
+                let Some(visible) = visible else {
+                    continue;
+                };
+
+                let mut found = 0.0;
+                let mut flux = Color::zero();
+                map.for_each_within(visible.rec.point, state.radius, |photon| {
+                    found += 1.0;
+                    flux += reflected(&visible, photon);
+                });
+                if found == 0.0 {
+                    continue;
+                }
+
+                let photons = state.photons + self.alpha * found;
+                let radius = state.radius * (photons / (state.photons + found)).sqrt();
+                let shrink = (radius / state.radius).powi(2);
+                state.flux = (state.flux + visible.beta * flux) * shrink;
+                state.photons = photons;
+                state.radius = radius;
+            }
+        }
+
+        let iterations = self.iterations as f64;
+        states
+            .iter()
+            .map(|state| {
+                state.direct / iterations
+                    + state.flux / (iterations * PI * state.radius * state.radius)
+            })
+            .collect()
+    }
+}
+
+impl Integrator for ProgressivePhotonMapper {
+    // Runs `iterations` passes in place of the camera's samples per pixel.
+    fn render(&self, camera: &Camera, scene: &Scene) -> Film {
+        let width = camera.image_width();
+        let pixels = (width * camera.image_height()) as usize;
+        let colors = self.estimate(scene, pixels, |index| {
+            camera.get_ray(index as u32 % width, index as u32 / width)
+        });
+
+        let mut film = Film::new(width, camera.image_height(), 1);
+        for (index, color) in colors.into_iter().enumerate() {
+            film.add_sample(index as u32 % width, index as u32 / width, color);
+        }
+
+        eprintln!("Done.");
+        film
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct PixelState {
+    radius: f64,
+    // Photons counted so far, after discarding a fraction of each iteration's.
+    photons: f64,
+    flux: Color,
+    direct: Color,
+}
+
+// Where a photon came to rest, by the bounces before it: none, only specular, or some diffuse.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Landing {
+    Direct,
+    Caustic,
+    Indirect,
+}
+
+// The first diffuse surface along a camera path, with the path's throughput up to it.
+#[derive(Clone, Debug)]
+struct VisiblePoint {
+    incoming: Ray,
+    rec: HitRecord,
+    beta: Color,
+}
+
+// Emits `count` photons from the lights and records each diffuse surface they land on.
+fn trace_photons(scene: &Scene, count: usize, max_depth: u32) -> Vec<(Photon, Landing)> {
+    let mut photons = Vec::new();
+
+    for _ in 0..count {
+        let Some((light, pdf_position)) = scene.lights().sample_surface() else {
+            break;
+        };
+
+        let emission = CosinePdf::new(light.normal);
+        let direction = emission.generate();
+        let pdf_direction = emission.value(direction);
+        if pdf_position <= 0.0 || pdf_direction <= 0.0 {
+            continue;
+        }
+
+        let arriving = Ray::new(light.point + direction, -direction);
+        let cosine = light.normal.dot(direction.unit_vector()).abs();
+        let mut power = light.material.emitted(&arriving, &light) * cosine
+            / (pdf_position * pdf_direction * count as f64);
+        let mut ray = Ray::new(light.point, direction);
+        let mut landing = Landing::Direct;
+
+        for bounce in 0..max_depth {
+            let mut rec = HitRecord::default();
+            if !scene
+                .world()
+                .hit(ray, Interval::new(0.001, f64::INFINITY), &mut rec)
+            {
+                break;
+            }
+
+            let mut scattered = Ray::default();
+            let mut attenuation = Color::default();
+            if !rec
+                .material
+                .scatter(&ray, &rec, &mut attenuation, &mut scattered)
+            {
+                break;
+            }
+
+            let pdf = rec.material.pdf(&ray, &rec, &scattered);
+            let weight = if pdf <= 0.0 || !rec.material.is_surface() {
+                if landing == Landing::Direct {
+                    landing = Landing::Caustic;
+                }
+                if pdf <= 0.0 {
+                    attenuation
+                } else {
+                    rec.material.evaluate(&ray, &rec, &scattered) / pdf
+                }
+            } else {
+                photons.push((
+                    Photon {
+                        position: rec.point,
+                        direction: ray.direction().unit_vector(),
+                        power,
+                    },
+                    landing,
+                ));
+                landing = Landing::Indirect;
+                rec.material.evaluate(&ray, &rec, &scattered) / pdf
+            };
+
+            let survival = survival_probability(bounce, weight);
+            if random_double() >= survival {
+                break;
+            }
+
+            power = power * weight / survival;
+            ray = scattered;
+        }
+    }
+
+    photons
+}
+
+// Follows `ray` through specular bounces and media to the first diffuse surface, returning it if
+// reached. With `gather_light`, also returns the light picked up on the way, including the direct
+// light at that surface.
+fn trace_to_diffuse(
+    ray: Ray,
+    scene: &Scene,
+    max_depth: u32,
+    gather_light: bool,
+) -> (Color, Option<VisiblePoint>) {
+    let mut color = Color::zero();
+    let mut beta = Color::new(1.0, 1.0, 1.0);
+    let mut ray = ray;
+
+    for _ in 0..max_depth {
+        let mut rec = HitRecord::default();
+        if !scene
+            .world()
+            .hit(ray, Interval::new(0.001, f64::INFINITY), &mut rec)
+        {
+            if gather_light {
+                color += beta * background(ray);
+            }
+            break;
+        }
+
+        if gather_light {
+            color += beta * rec.material.emitted(&ray, &rec);
+        }
+
+        let mut scattered = Ray::default();
+        let mut attenuation = Color::default();
+        if !rec
+            .material
+            .scatter(&ray, &rec, &mut attenuation, &mut scattered)
+        {
+            break;
+        }
+
+        let pdf = rec.material.pdf(&ray, &rec, &scattered);
+        if pdf <= 0.0 {
+            beta *= attenuation;
+        } else if !rec.material.is_surface() {
+            beta = beta * rec.material.evaluate(&ray, &rec, &scattered) / pdf;
+        } else {
+            if gather_light {
+                color += beta * estimate_direct(&ray, &rec, &scattered, pdf, scene);
+            }
+            let visible = VisiblePoint {
+                incoming: ray,
+                rec,
+                beta,
+            };
+            return (color, Some(visible));
+        }
+
+        ray = scattered;
+    }
+
+    (color, None)
+}
+
+// Light reflected towards the camera by a single photon.
+fn reflected(visible: &VisiblePoint, photon: &Photon) -> Color {
+    let toward_light = Ray::new(visible.rec.point, -photon.direction);
+    let cosine = visible.rec.normal.dot(photon.direction).abs();
+    if cosine <= 0.0 {
+        return Color::zero();
+    }
+
+    let rec = &visible.rec;
+    rec.material.evaluate(&visible.incoming, rec, &toward_light) / cosine * photon.power
+}
+
+// Radiance towards the camera estimated from the photons within `radius` of a visible point.
+fn density(map: &PhotonMap, visible: &VisiblePoint, radius: f64) -> Color {
+    let mut flux = Color::zero();
+    map.for_each_within(visible.rec.point, radius, |photon| {
+        flux += reflected(visible, photon);
+    });
+
+    flux / (PI * radius * radius)
+}
@@ -0,0 +1,65 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::sync::Arc;
+
+#[derive(Clone, Debug)]
+pub struct Plane {
+    point: Point3,
+    normal: Vec3,
+    material: Arc<dyn Material>,
+}
+
+impl Plane {
+    pub fn new(point: Point3, normal: Vec3, material: Arc<dyn Material>) -> Self {
+        Self {
+            point,
+            normal: normal.unit_vector(),
+            material,
+        }
+    }
+
+    pub fn point(&self) -> Point3 {
+        self.point
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        self.normal
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let denominator = self.normal.dot(ray.direction());
+        if denominator.abs() < 1e-8 {
+            return false;
+        }
+
+        let root = (self.point - ray.origin()).dot(self.normal) / denominator;
+        if !ray_t.surrounds(root) {
+            return false;
+        }
+
+        record.t = root;
+        record.point = ray.at(root);
+        record.material = self.material.clone();
+        record.set_face_normal(ray, self.normal);
+
+        let basis = Onb::new(self.normal);
+        let offset = record.point - self.point;
+        record.tangent = basis.u();
+        record.u = offset.dot(basis.u());
+        record.v = offset.dot(basis.v());
+        record.uv_scale = (1.0, 1.0);
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::universe()
+    }
+}
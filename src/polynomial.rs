@@ -0,0 +1,217 @@
+use std::f64::consts::PI;
+
+const EPSILON: f64 = 1e-12;
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < EPSILON
+}
+
+// Real roots of c2 x^2 + c1 x + c0, in ascending order.
+pub fn solve_quadratic(c2: f64, c1: f64, c0: f64) -> Vec<f64> {
+    if is_zero(c2) {
+        if is_zero(c1) {
+            return Vec::new();
+        }
+        return vec![-c0 / c1];
+    }
+
+    let discriminant = c1 * c1 - 4.0 * c2 * c0;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    if discriminant == 0.0 {
+        return vec![-c1 / (2.0 * c2)];
+    }
+
+    // Avoids cancellation between -c1 and the square root.
+    let q = -0.5 * (c1 + c1.signum() * discriminant.sqrt());
+    let (r0, r1) = if q == 0.0 {
+        let root = (-c0 / c2).sqrt();
+        (-root, root)
+    } else {
+        (q / c2, c0 / q)
+    };
+
+    vec![r0.min(r1), r0.max(r1)]
+}
+
+// Real roots of c3 x^3 + c2 x^2 + c1 x + c0, in ascending order.
+pub fn solve_cubic(c3: f64, c2: f64, c1: f64, c0: f64) -> Vec<f64> {
+    if is_zero(c3) {
+        return solve_quadratic(c2, c1, c0);
+    }
+
+    let a = c2 / c3;
+    let b = c1 / c3;
+    let c = c0 / c3;
+
+    // Substitute x = y - a/3 to obtain the depressed cubic y^3 + 3py + 2q = 0.
+    let a_squared = a * a;
+    let p = (-a_squared / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * a_squared - a * b / 3.0 + c) / 2.0;
+
+    let p_cubed = p * p * p;
+    let discriminant = q * q + p_cubed;
+
+    let mut roots = if is_zero(discriminant) {
+        if is_zero(q) {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        let phi = (-q / (-p_cubed).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.0).cos(),
+            -t * (phi - PI / 3.0).cos(),
+        ]
+    } else {
+        let sqrt_discriminant = discriminant.sqrt();
+        vec![(sqrt_discriminant - q).cbrt() - (sqrt_discriminant + q).cbrt()]
+    };
+
+    for root in roots.iter_mut() {
+        *root -= a / 3.0;
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+// Real roots of c4 x^4 + c3 x^3 + c2 x^2 + c1 x + c0, in ascending order. Roots from Ferrari's
+// method are polished with Newton iterations against the original polynomial.
+pub fn solve_quartic(c4: f64, c3: f64, c2: f64, c1: f64, c0: f64) -> Vec<f64> {
+    if is_zero(c4) {
+        return solve_cubic(c3, c2, c1, c0);
+    }
+
+    let a = c3 / c4;
+    let b = c2 / c4;
+    let c = c1 / c4;
+    let d = c0 / c4;
+
+    // Substitute x = y - a/4 to obtain the depressed quartic y^4 + py^2 + qy + r = 0.
+    let a_squared = a * a;
+    let p = -3.0 / 8.0 * a_squared + b;
+    let q = a_squared * a / 8.0 - a * b / 2.0 + c;
+    let r = -3.0 / 256.0 * a_squared * a_squared + a_squared * b / 16.0 - a * c / 4.0 + d;
+
+    let roots = if is_zero(r) {
+        let mut roots = solve_cubic(1.0, 0.0, p, q);
+        roots.push(0.0);
+        roots
+    } else {
+        // A real root of the resolvent cubic splits the quartic into two quadratics; the largest
+        // one keeps both square roots below real.
+        let resolvent = solve_cubic(1.0, -p / 2.0, -r, r * p / 2.0 - q * q / 8.0);
+        let Some(&z) = resolvent.last() else {
+            return Vec::new();
+        };
+
+        let u = (z * z - r).max(0.0).sqrt();
+        let v = (2.0 * z - p).max(0.0).sqrt();
+        let v = if q < 0.0 { -v } else { v };
+
+        let mut roots = solve_quadratic(1.0, v, z - u);
+        roots.extend(solve_quadratic(1.0, -v, z + u));
+        roots
+    };
+
+    let mut roots: Vec<f64> = roots
+        .into_iter()
+        .filter_map(|root| polish_quartic_root(root - a / 4.0, [c4, c3, c2, c1, c0]))
+        .collect();
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+// Refines `x` with Newton's method, rejecting it if cancellation left it far from a true root.
+fn polish_quartic_root(mut x: f64, coefficients: [f64; 5]) -> Option<f64> {
+    let [c4, c3, c2, c1, c0] = coefficients;
+
+    for _ in 0..8 {
+        let value = (((c4 * x + c3) * x + c2) * x + c1) * x + c0;
+        let derivative = ((4.0 * c4 * x + 3.0 * c3) * x + 2.0 * c2) * x + c1;
+        if derivative == 0.0 {
+            break;
+        }
+
+        let step = value / derivative;
+        x -= step;
+        if step.abs() <= EPSILON * x.abs().max(1.0) {
+            break;
+        }
+    }
+
+    let value = (((c4 * x + c3) * x + c2) * x + c1) * x + c0;
+    let magnitude = (((c4.abs() * x.abs() + c3.abs()) * x.abs() + c2.abs()) * x.abs() + c1.abs())
+        * x.abs()
+        + c0.abs();
+    (value.abs() <= 1e-6 * magnitude).then_some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(roots: Vec<f64>, expected: &[f64]) {
+        assert_eq!(
+            roots.len(),
+            expected.len(),
+            "roots {roots:?}, expected {expected:?}"
+        );
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!(
+                (root - expected).abs() < 1e-9,
+                "roots {roots:?}, expected {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn quadratic_roots() {
+        assert_roots(solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0]);
+        assert_roots(solve_quadratic(2.0, 0.0, -8.0), &[-2.0, 2.0]);
+        assert_roots(solve_quadratic(0.0, 2.0, -1.0), &[0.5]);
+        assert_roots(solve_quadratic(1.0, 0.0, 1.0), &[]);
+    }
+
+    #[test]
+    fn cubic_roots() {
+        // (x - 1)(x - 2)(x - 3)
+        assert_roots(solve_cubic(1.0, -6.0, 11.0, -6.0), &[1.0, 2.0, 3.0]);
+        // (x + 2)(x^2 + 1)
+        assert_roots(solve_cubic(1.0, 2.0, 1.0, 2.0), &[-2.0]);
+        // (x - 1)^2 (x + 2)
+        assert_roots(solve_cubic(1.0, 0.0, -3.0, 2.0), &[-2.0, 1.0]);
+    }
+
+    #[test]
+    fn quartic_roots() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(
+            solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0),
+            &[1.0, 2.0, 3.0, 4.0],
+        );
+        // (x^2 + 1)(x^2 - 4)
+        assert_roots(solve_quartic(1.0, 0.0, -3.0, 0.0, -4.0), &[-2.0, 2.0]);
+        // 2 (x + 0.5)(x - 1.5)(x^2 + 2x + 5), with an odd term that gives the depressed quartic a
+        // negative q.
+        assert_roots(solve_quartic(2.0, 2.0, 4.5, -13.0, -7.5), &[-0.5, 1.5]);
+        assert_roots(solve_quartic(1.0, 0.0, 0.0, 0.0, 1.0), &[]);
+        assert_roots(solve_quartic(0.0, 1.0, -6.0, 11.0, -6.0), &[1.0, 2.0, 3.0]);
+    }
+
+    // A ray through the middle of a torus with major radius 2 and minor radius 1, along its
+    // plane, crosses the tube at distances 1 and 3 either side of the center.
+    #[test]
+    fn quartic_roots_of_a_torus_crossing() {
+        // (t^2 + 2^2 - 1^2)^2 - 4 * 2^2 * t^2, from the torus equation along x = t.
+        assert_roots(
+            solve_quartic(1.0, 0.0, -10.0, 0.0, 9.0),
+            &[-3.0, -1.0, 1.0, 3.0],
+        );
+    }
+}
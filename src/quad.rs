@@ -0,0 +1,146 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable, SURFACE_TOLERANCE};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::utility::random_double;
+use crate::vec3::{Point3, Vec3};
+use std::sync::Arc;
+
+// Parallelogram with a corner at `corner` and sides `u` and `v`; the front faces along u x v.
+#[derive(Clone, Debug)]
+pub struct Quad {
+    corner: Point3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    // Projects an offset in the quad's plane onto the (u, v) coordinates of the point.
+    w: Vec3,
+    area: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Quad {
+    pub fn new(corner: Point3, u: Vec3, v: Vec3, material: Arc<dyn Material>) -> Self {
+        let n = u.cross(v);
+
+        Self {
+            corner,
+            u,
+            v,
+            normal: n.unit_vector(),
+            w: n / n.length_squared(),
+            area: n.length(),
+            material,
+        }
+    }
+
+    pub fn corner(&self) -> Point3 {
+        self.corner
+    }
+
+    pub fn u(&self) -> Vec3 {
+        self.u
+    }
+
+    pub fn v(&self) -> Vec3 {
+        self.v
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let denominator = self.normal.dot(ray.direction());
+        if denominator.abs() < 1e-8 {
+            return false;
+        }
+
+        let root = (self.corner - ray.origin()).dot(self.normal) / denominator;
+        if !ray_t.surrounds(root) {
+            return false;
+        }
+
+        let point = ray.at(root);
+        let offset = point - self.corner;
+        let alpha = self.w.dot(offset.cross(self.v));
+        let beta = self.w.dot(self.u.cross(offset));
+
+        let unit = Interval::new(0.0, 1.0);
+        if !unit.contains(alpha) || !unit.contains(beta) {
+            return false;
+        }
+
+        record.t = root;
+        record.point = point;
+        record.material = self.material.clone();
+        record.set_face_normal(ray, self.normal);
+        record.tangent = self.u.unit_vector();
+        record.u = alpha;
+        record.v = beta;
+        record.uv_scale = (self.u.length(), self.v.length());
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::surrounding(
+            Aabb::from_points(self.corner, self.corner + self.u + self.v),
+            Aabb::from_points(self.corner + self.u, self.corner + self.v),
+        )
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let mut record = HitRecord::default();
+        if !self.hit(
+            Ray::new(origin, direction),
+            Interval::new(0.001, f64::INFINITY),
+            &mut record,
+        ) {
+            return 0.0;
+        }
+
+        let distance_squared = record.t * record.t * direction.length_squared();
+        let cosine = direction.dot(self.normal).abs() / direction.length();
+
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        let point = self.corner + self.u * random_double() + self.v * random_double();
+        point - origin
+    }
+
+    fn sample_surface(&self) -> Option<(HitRecord, f64)> {
+        let (alpha, beta) = (random_double(), random_double());
+        let record = HitRecord {
+            point: self.corner + self.u * alpha + self.v * beta,
+            normal: self.normal,
+            tangent: self.u.unit_vector(),
+            material: self.material.clone(),
+            u: alpha,
+            v: beta,
+            uv_scale: (self.u.length(), self.v.length()),
+            front_face: true,
+            ..HitRecord::default()
+        };
+
+        Some((record, 1.0 / self.area))
+    }
+
+    fn surface_pdf(&self, point: Point3) -> f64 {
+        let offset = point - self.corner;
+        let tolerance = SURFACE_TOLERANCE * (self.u.length() + self.v.length());
+        if offset.dot(self.normal).abs() > tolerance {
+            return 0.0;
+        }
+
+        let alpha = self.w.dot(offset.cross(self.v));
+        let beta = self.w.dot(self.u.cross(offset));
+        let unit = Interval::new(-SURFACE_TOLERANCE, 1.0 + SURFACE_TOLERANCE);
+        if unit.contains(alpha) && unit.contains(beta) {
+            1.0 / self.area
+        } else {
+            0.0
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+// A source of uniform numbers in [0, 1). Everything random while rendering draws from the
+// sampler installed on its thread, through `utility::random_double`.
+pub trait Sampler {
+    fn sample(&mut self) -> f64;
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<RefCell<dyn Sampler>>>> = const { RefCell::new(None) };
+    static DEFAULT: RefCell<RandomSampler> = RefCell::new(RandomSampler::from_entropy());
+}
+
+// Installs `sampler` on this thread while `f` runs, restoring the previous one afterwards.
+pub fn with_sampler<R>(sampler: Rc<RefCell<dyn Sampler>>, f: impl FnOnce() -> R) -> R {
+    let previous = CURRENT.with(|current| current.replace(Some(sampler)));
+    let result = f();
+    CURRENT.with(|current| current.replace(previous));
+    result
+}
+
+// Reseeds the sampler used on this thread when no other is installed, making renders repeatable.
+pub fn seed(seed: u64) {
+    DEFAULT.with(|sampler| *sampler.borrow_mut() = RandomSampler::new(seed));
+}
+
+pub(crate) fn next_sample() -> f64 {
+    CURRENT.with(|current| match current.borrow().as_ref() {
+        Some(sampler) => sampler.borrow_mut().sample(),
+        None => DEFAULT.with(|sampler| sampler.borrow_mut().sample()),
+    })
+}
+
+// Independent uniform numbers from a seeded generator.
+#[derive(Clone, Debug)]
+pub struct RandomSampler {
+    rng: StdRng,
+}
+
+impl RandomSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn from_entropy() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+        }
+    }
+}
+
+impl Sampler for RandomSampler {
+    fn sample(&mut self) -> f64 {
+        self.rng.gen()
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct PrimarySample {
+    value: f64,
+    last_modified: u64,
+    backup_value: f64,
+    backup_modified: u64,
+}
+
+// Numbers for Metropolis sampling in primary sample space: each iteration replays the numbers of
+// the last accepted one, either perturbed slightly (a small step) or replaced outright (a large
+// step). Perturbations are applied lazily, when a number is next drawn, so paths may draw as many
+// numbers as they need. The first iteration is a large step drawn from `seed`, so that seeding
+// two samplers alike reproduces the same initial path.
+#[derive(Clone, Debug)]
+pub struct PrimarySampleSpace {
+    rng: StdRng,
+    sigma: f64,
+    large_step_probability: f64,
+    samples: Vec<PrimarySample>,
+    index: usize,
+    iteration: u64,
+    last_large_step: u64,
+    large_step: bool,
+}
+
+impl PrimarySampleSpace {
+    pub fn new(seed: u64, sigma: f64, large_step_probability: f64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            sigma,
+            large_step_probability,
+            samples: Vec::new(),
+            index: 0,
+            iteration: 0,
+            last_large_step: 0,
+            large_step: true,
+        }
+    }
+
+    // Begins proposing a mutation of the current sample vector.
+    pub fn start_iteration(&mut self) {
+        self.iteration += 1;
+        self.large_step = self.rng.gen::<f64>() < self.large_step_probability;
+        self.index = 0;
+    }
+
+    pub fn accept(&mut self) {
+        if self.large_step {
+            self.last_large_step = self.iteration;
+        }
+    }
+
+    // Returns to the numbers of the last accepted iteration.
+    pub fn reject(&mut self) {
+        for sample in &mut self.samples {
+            if sample.last_modified == self.iteration {
+                sample.value = sample.backup_value;
+                sample.last_modified = sample.backup_modified;
+            }
+        }
+        self.iteration -= 1;
+    }
+
+    // A standard normal number, by the Box-Muller transform.
+    fn gaussian(&mut self) -> f64 {
+        let u1 = 1.0 - self.rng.gen::<f64>();
+        let u2 = self.rng.gen::<f64>();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+}
+
+impl Sampler for PrimarySampleSpace {
+    fn sample(&mut self) -> f64 {
+        // Numbers drawn for the first time are as if drawn by the last accepted large step.
+        if self.index >= self.samples.len() {
+            let sample = PrimarySample {
+                value: self.rng.gen(),
+                last_modified: self.last_large_step,
+                ..PrimarySample::default()
+            };
+            self.samples.push(sample);
+        }
+
+        let mut sample = self.samples[self.index];
+        self.index += 1;
+
+        // Numbers not drawn since the last accepted large step would have been replaced by it.
+        if sample.last_modified < self.last_large_step {
+            sample.value = self.rng.gen();
+            sample.last_modified = self.last_large_step;
+        }
+
+        sample.backup_value = sample.value;
+        sample.backup_modified = sample.last_modified;
+        if self.large_step {
+            sample.value = self.rng.gen();
+        } else {
+            // Every small step missed since the number was last drawn perturbs it in turn.
+            let steps = (self.iteration - sample.last_modified) as f64;
+            sample.value += self.gaussian() * self.sigma * steps.sqrt();
+            sample.value -= sample.value.floor();
+        }
+        sample.last_modified = self.iteration;
+
+        self.samples[self.index - 1] = sample;
+        sample.value
+    }
+}
@@ -0,0 +1,26 @@
+use crate::hittable::Hittable;
+use crate::hittable_list::HittableList;
+
+// Everything an integrator renders: the geometry, and the emitters in it that can be sampled
+// directly.
+pub struct Scene {
+    world: Box<dyn Hittable>,
+    lights: HittableList,
+}
+
+impl Scene {
+    pub fn new(world: impl Hittable + 'static, lights: HittableList) -> Self {
+        Self {
+            world: Box::new(world),
+            lights,
+        }
+    }
+
+    pub fn world(&self) -> &dyn Hittable {
+        self.world.as_ref()
+    }
+
+    pub fn lights(&self) -> &HittableList {
+        &self.lights
+    }
+}
@@ -0,0 +1,158 @@
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::hittable_list::HittableList;
+use crate::material::{Dielectric, DiffuseLight, Lambertian, Material, Metal};
+use crate::plane::Plane;
+use crate::quad::Quad;
+use crate::scene::Scene;
+use crate::sphere::Sphere;
+use crate::utility::{random_double, random_double_range};
+use crate::vec3::{Point3, Vec3};
+use std::sync::Arc;
+
+// A field of small random spheres around three large ones, lit only by the sky.
+pub fn random_spheres(image_width: u32, samples_per_pixel: u32) -> (Scene, Camera) {
+    let mut world = HittableList::new();
+
+    let ground_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    world.add(Plane::new(
+        Point3::zero(),
+        Vec3::new(0.0, 1.0, 0.0),
+        ground_material,
+    ));
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let choose_material = random_double();
+            let center = Point3::new(
+                a as f64 + 0.9 * random_double(),
+                0.2,
+                b as f64 + 0.9 * random_double(),
+            );
+
+            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
+                let sphere_material: Arc<dyn Material> = if choose_material < 0.8 {
+                    let albedo = Color::random() * Color::random();
+                    Arc::new(Lambertian::new(albedo))
+                } else if choose_material < 0.95 {
+                    let albedo = Color::random_range(0.5, 1.0);
+                    let fuzz = random_double_range(0.0, 0.5);
+                    Arc::new(Metal::new(albedo, fuzz))
+                } else {
+                    Arc::new(Dielectric::new(1.5))
+                };
+
+                world.add(Sphere::new(center, 0.2, sphere_material));
+            }
+        }
+    }
+
+    let material1 = Arc::new(Dielectric::new(1.5));
+    world.add(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, material1));
+
+    let material2 = Arc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1)));
+    world.add(Sphere::new(Point3::new(-4.0, 1.0, 0.0), 1.0, material2));
+
+    let material3 = Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
+    world.add(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, material3));
+
+    let camera = Camera::new(
+        16.0 / 9.0,
+        image_width,
+        samples_per_pixel,
+        20.0,
+        Point3::new(13.0, 2.0, 3.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        0.5,
+        10.0,
+    );
+
+    (Scene::new(world, HittableList::new()), camera)
+}
+
+// A closed Cornell-style room lit by a single panel in the ceiling, with a glass sphere and a
+// diffuse one. No sky reaches inside, so every integrator sees the same light, including those
+// that only carry light from sampled emitters.
+pub fn lit_room(image_width: u32, samples_per_pixel: u32) -> (Scene, Camera) {
+    let red: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light: Arc<dyn Material> = Arc::new(DiffuseLight::new(Color::new(15.0, 15.0, 15.0)));
+
+    // The side walls, floor and ceiling run back past the camera to a wall behind it.
+    let (size, front) = (5.55, -8.01);
+    let depth = size - front;
+
+    let mut world = HittableList::new();
+    world.add(Quad::new(
+        Point3::new(size, 0.0, front),
+        Vec3::new(0.0, size, 0.0),
+        Vec3::new(0.0, 0.0, depth),
+        green,
+    ));
+    world.add(Quad::new(
+        Point3::new(0.0, 0.0, front),
+        Vec3::new(0.0, size, 0.0),
+        Vec3::new(0.0, 0.0, depth),
+        red,
+    ));
+    world.add(Quad::new(
+        Point3::new(0.0, 0.0, front),
+        Vec3::new(size, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, depth),
+        white.clone(),
+    ));
+    world.add(Quad::new(
+        Point3::new(size, size, size),
+        Vec3::new(-size, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -depth),
+        white.clone(),
+    ));
+    world.add(Quad::new(
+        Point3::new(0.0, 0.0, size),
+        Vec3::new(size, 0.0, 0.0),
+        Vec3::new(0.0, size, 0.0),
+        white.clone(),
+    ));
+    world.add(Quad::new(
+        Point3::new(0.0, 0.0, front),
+        Vec3::new(size, 0.0, 0.0),
+        Vec3::new(0.0, size, 0.0),
+        white.clone(),
+    ));
+
+    world.add(Sphere::new(
+        Point3::new(3.7, 0.9, 3.5),
+        0.9,
+        Arc::new(Dielectric::new(1.5)),
+    ));
+    world.add(Sphere::new(Point3::new(1.9, 0.9, 1.9), 0.9, white));
+
+    // Emitters go in the world, for rays to hit, and in the lights, for integrators to sample
+    // directly. An emitter missing from the lights still shines, but only on paths that happen
+    // to hit it, and light paths never start from it.
+    let panel = Quad::new(
+        Point3::new(3.43, 5.54, 3.32),
+        Vec3::new(-1.3, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -1.05),
+        light,
+    );
+    let mut lights = HittableList::new();
+    lights.add(panel.clone());
+    world.add(panel);
+
+    let camera = Camera::new(
+        1.0,
+        image_width,
+        samples_per_pixel,
+        40.0,
+        Point3::new(2.78, 2.78, -8.0),
+        Point3::new(2.78, 2.78, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        0.0,
+        10.0,
+    );
+
+    (Scene::new(world, lights), camera)
+}
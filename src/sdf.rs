@@ -0,0 +1,314 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::sync::Arc;
+
+const MAX_STEPS: u32 = 512;
+const SURFACE_EPSILON: f64 = 1e-4;
+const GRADIENT_EPSILON: f64 = 1e-5;
+
+// A signed distance to a surface: negative inside, positive outside, and never overestimating
+// the distance to the nearest surface point.
+pub trait Sdf {
+    fn distance(&self, p: Point3) -> f64;
+}
+
+impl<F: Fn(Point3) -> f64> Sdf for F {
+    fn distance(&self, p: Point3) -> f64 {
+        self(p)
+    }
+}
+
+fn gradient(sdf: &impl Sdf, p: Point3) -> Vec3 {
+    let dx = Vec3::new(GRADIENT_EPSILON, 0.0, 0.0);
+    let dy = Vec3::new(0.0, GRADIENT_EPSILON, 0.0);
+    let dz = Vec3::new(0.0, 0.0, GRADIENT_EPSILON);
+
+    Vec3::new(
+        sdf.distance(p + dx) - sdf.distance(p - dx),
+        sdf.distance(p + dy) - sdf.distance(p - dy),
+        sdf.distance(p + dz) - sdf.distance(p - dz),
+    )
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SdfSphere {
+    center: Point3,
+    radius: f64,
+}
+
+impl SdfSphere {
+    pub fn new(center: Point3, radius: f64) -> Self {
+        Self { center, radius }
+    }
+}
+
+impl Sdf for SdfSphere {
+    fn distance(&self, p: Point3) -> f64 {
+        (p - self.center).length() - self.radius
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SdfBox {
+    center: Point3,
+    half_extents: Vec3,
+}
+
+impl SdfBox {
+    pub fn new(center: Point3, half_extents: Vec3) -> Self {
+        Self {
+            center,
+            half_extents,
+        }
+    }
+}
+
+impl Sdf for SdfBox {
+    fn distance(&self, p: Point3) -> f64 {
+        let local = p - self.center;
+        let q = Vec3::new(
+            local.x().abs() - self.half_extents.x(),
+            local.y().abs() - self.half_extents.y(),
+            local.z().abs() - self.half_extents.z(),
+        );
+        let outside = Vec3::new(q.x().max(0.0), q.y().max(0.0), q.z().max(0.0));
+
+        outside.length() + q.x().max(q.y()).max(q.z()).min(0.0)
+    }
+}
+
+// A torus whose ring lies in the xz plane around `center`.
+#[derive(Copy, Clone, Debug)]
+pub struct SdfTorus {
+    center: Point3,
+    major_radius: f64,
+    minor_radius: f64,
+}
+
+impl SdfTorus {
+    pub fn new(center: Point3, major_radius: f64, minor_radius: f64) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Sdf for SdfTorus {
+    fn distance(&self, p: Point3) -> f64 {
+        let local = p - self.center;
+        let ring_distance = (local.x() * local.x() + local.z() * local.z()).sqrt();
+        let x = ring_distance - self.major_radius;
+
+        (x * x + local.y() * local.y()).sqrt() - self.minor_radius
+    }
+}
+
+// Blends two distance fields over a region about `smoothness` wide.
+#[derive(Copy, Clone, Debug)]
+pub struct SmoothUnion<A: Sdf, B: Sdf> {
+    a: A,
+    b: B,
+    smoothness: f64,
+}
+
+impl<A: Sdf, B: Sdf> SmoothUnion<A, B> {
+    pub fn new(a: A, b: B, smoothness: f64) -> Self {
+        Self { a, b, smoothness }
+    }
+}
+
+impl<A: Sdf, B: Sdf> Sdf for SmoothUnion<A, B> {
+    fn distance(&self, p: Point3) -> f64 {
+        let d1 = self.a.distance(p);
+        let d2 = self.b.distance(p);
+        if self.smoothness <= 0.0 {
+            return d1.min(d2);
+        }
+
+        let h = (0.5 + 0.5 * (d2 - d1) / self.smoothness).clamp(0.0, 1.0);
+        d2 + (d1 - d2) * h - self.smoothness * h * (1.0 - h)
+    }
+}
+
+// Repeats a field infinitely with the given period along each axis; a zero period leaves that
+// axis unrepeated.
+#[derive(Copy, Clone, Debug)]
+pub struct Repetition<S: Sdf> {
+    inner: S,
+    period: Vec3,
+}
+
+impl<S: Sdf> Repetition<S> {
+    pub fn new(inner: S, period: Vec3) -> Self {
+        Self { inner, period }
+    }
+}
+
+impl<S: Sdf> Sdf for Repetition<S> {
+    fn distance(&self, p: Point3) -> f64 {
+        let wrap = |x: f64, period: f64| {
+            if period > 0.0 {
+                x - period * (x / period).round()
+            } else {
+                x
+            }
+        };
+
+        self.inner.distance(Point3::new(
+            wrap(p.x(), self.period.x()),
+            wrap(p.y(), self.period.y()),
+            wrap(p.z(), self.period.z()),
+        ))
+    }
+}
+
+// Twists a field around the y axis by `rate` radians per unit of height.
+#[derive(Copy, Clone, Debug)]
+pub struct Twist<S: Sdf> {
+    inner: S,
+    rate: f64,
+}
+
+impl<S: Sdf> Twist<S> {
+    pub fn new(inner: S, rate: f64) -> Self {
+        Self { inner, rate }
+    }
+}
+
+impl<S: Sdf> Sdf for Twist<S> {
+    fn distance(&self, p: Point3) -> f64 {
+        let angle = self.rate * p.y();
+        let (sin, cos) = angle.sin_cos();
+        let twisted = Point3::new(cos * p.x() - sin * p.z(), p.y(), sin * p.x() + cos * p.z());
+
+        // Twisting stretches distances by up to this factor, so scale them back down to keep
+        // the field safe to sphere trace.
+        let radius = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        let stretch = (1.0 + (self.rate * radius).powi(2)).sqrt();
+
+        self.inner.distance(twisted) / stretch
+    }
+}
+
+pub struct SdfObject<S: Sdf> {
+    sdf: S,
+    bounds: Aabb,
+    material: Arc<dyn Material>,
+}
+
+impl<S: Sdf> SdfObject<S> {
+    pub fn new(sdf: S, bounds: Aabb, material: Arc<dyn Material>) -> Self {
+        Self {
+            sdf,
+            bounds,
+            material,
+        }
+    }
+}
+
+impl<S: Sdf> Hittable for SdfObject<S> {
+    // Sphere traces from wherever the ray enters the bounds, marching on the absolute distance
+    // so rays that start inside the object (such as refracted rays) find their way out.
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let Some(segment) = self.bounds.hit(ray, ray_t) else {
+            return false;
+        };
+
+        let direction_length = ray.direction().length();
+        let mut t = segment.min();
+
+        let start = ray.at(t);
+        let start_distance = self.sdf.distance(start);
+        let side = if start_distance.abs() < SURFACE_EPSILON {
+            if gradient(&self.sdf, start).dot(ray.direction()) > 0.0 {
+                1.0
+            } else {
+                -1.0
+            }
+        } else {
+            start_distance.signum()
+        };
+
+        // Rays leaving the surface they start on must clear the surface band before a hit counts.
+        let mut armed = start_distance.abs() >= SURFACE_EPSILON;
+        let mut found = false;
+        for _ in 0..MAX_STEPS {
+            let distance = side * self.sdf.distance(ray.at(t));
+            if distance >= SURFACE_EPSILON {
+                armed = true;
+            } else if armed {
+                found = true;
+                break;
+            }
+
+            t += distance.max(SURFACE_EPSILON) / direction_length;
+            if t > segment.max() {
+                return false;
+            }
+        }
+
+        if !found || !ray_t.surrounds(t) {
+            return false;
+        }
+
+        record.t = t;
+        record.point = ray.at(t);
+        record.material = self.material.clone();
+        let outward_normal = gradient(&self.sdf, record.point).unit_vector();
+        record.set_face_normal(ray, outward_normal);
+        (record.u, record.v, record.tangent, record.uv_scale) =
+            box_mapping(self.bounds, record.point, outward_normal);
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounds
+    }
+}
+
+// Fields have no parameterization of their own, so textures are projected onto the bounds along
+// whichever axis the surface faces most, with u and v running along the other two. The tangent
+// follows u across the surface.
+fn box_mapping(bounds: Aabb, point: Point3, normal: Vec3) -> (f64, f64, Vec3, (f64, f64)) {
+    let components = |v: Vec3| [v.x(), v.y(), v.z()];
+    let facing = components(normal);
+    let (u_axis, v_axis) = match (0..3)
+        .max_by(|&a, &b| facing[a].abs().total_cmp(&facing[b].abs()))
+        .unwrap_or(1)
+    {
+        0 => (2, 1),
+        1 => (0, 2),
+        _ => (0, 1),
+    };
+
+    let position = components(point);
+    let coordinate = |axis: usize| {
+        let extent = bounds.axis(axis);
+        (position[axis] - extent.min()) / extent.size()
+    };
+
+    let mut along_u = [0.0; 3];
+    along_u[u_axis] = 1.0;
+    let along_u = Vec3::new(along_u[0], along_u[1], along_u[2]);
+    let tangent = along_u - normal * normal.dot(along_u);
+    let tangent = if tangent.near_zero() {
+        Onb::new(normal).u()
+    } else {
+        tangent.unit_vector()
+    };
+
+    (
+        coordinate(u_axis),
+        coordinate(v_axis),
+        tangent,
+        (bounds.axis(u_axis).size(), bounds.axis(v_axis).size()),
+    )
+}
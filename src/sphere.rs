@@ -1,18 +1,23 @@
-use crate::hittable::{HitRecord, Hittable};
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable, Span, SURFACE_TOLERANCE};
 use crate::interval::Interval;
 use crate::material::Material;
+use crate::onb::Onb;
 use crate::ray::Ray;
-use crate::vec3::Point3;
+use crate::utility::random_double;
+use crate::vec3::{Point3, Vec3};
+use std::f64::consts::PI;
+use std::sync::Arc;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Sphere {
     center: Point3,
     radius: f64,
-    material: Material,
+    material: Arc<dyn Material>,
 }
 
 impl Sphere {
-    pub fn new(center: Point3, radius: f64, material: Material) -> Self {
+    pub fn new(center: Point3, radius: f64, material: Arc<dyn Material>) -> Self {
         Self {
             center,
             radius,
@@ -20,11 +25,11 @@ impl Sphere {
         }
     }
 
-    pub fn center(self) -> Point3 {
+    pub fn center(&self) -> Point3 {
         self.center
     }
 
-    pub fn radius(self) -> f64 {
+    pub fn radius(&self) -> f64 {
         self.radius
     }
 }
@@ -54,11 +59,140 @@ impl Hittable for Sphere {
         record.t = root;
         record.point = ray.at(root);
         record.normal = (record.point - self.center()) / self.radius();
-        record.material = self.material;
+        record.material = self.material.clone();
 
         let outward_normal = (record.point - self.center()) / self.radius();
         record.set_face_normal(ray, outward_normal);
+        record.tangent = sphere_tangent(outward_normal);
+        (record.u, record.v) = sphere_uv(outward_normal);
+        record.uv_scale = sphere_uv_scale(outward_normal, self.radius());
 
         true
     }
+
+    fn spans(&self, ray: Ray) -> Vec<Span> {
+        let oc = ray.origin() - self.center();
+        let a = ray.direction().length_squared();
+        let half_b = oc.dot(ray.direction());
+        let c = oc.length_squared() - self.radius() * self.radius();
+
+        let discriminant = half_b * half_b - a * c;
+        if discriminant <= 0.0 {
+            return Vec::new();
+        }
+
+        let sqrtd = discriminant.sqrt();
+        let record_at = |root: f64| {
+            let mut record = HitRecord {
+                t: root,
+                point: ray.at(root),
+                material: self.material.clone(),
+                ..HitRecord::default()
+            };
+            let outward_normal = (record.point - self.center()) / self.radius();
+            record.set_face_normal(ray, outward_normal);
+            record.tangent = sphere_tangent(outward_normal);
+            (record.u, record.v) = sphere_uv(outward_normal);
+            record.uv_scale = sphere_uv_scale(outward_normal, self.radius());
+            record
+        };
+
+        vec![Span {
+            enter: record_at((-half_b - sqrtd) / a),
+            exit: record_at((-half_b + sqrtd) / a),
+        }]
+    }
+
+    // Samples the cone of directions the sphere subtends, which only exists from outside it.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let mut record = HitRecord::default();
+        if !self.hit(
+            Ray::new(origin, direction),
+            Interval::new(0.001, f64::INFINITY),
+            &mut record,
+        ) {
+            return 0.0;
+        }
+
+        let distance_squared = (self.center - origin).length_squared();
+        if distance_squared <= self.radius * self.radius {
+            return 0.0;
+        }
+
+        let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared).sqrt();
+        let solid_angle = 2.0 * PI * (1.0 - cos_theta_max);
+
+        1.0 / solid_angle
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        let direction = self.center - origin;
+        let distance_squared = direction.length_squared();
+        if distance_squared <= self.radius * self.radius {
+            return Vec3::random_unit_vector();
+        }
+
+        let basis = Onb::new(direction);
+        let r1 = random_double();
+        let r2 = random_double();
+        let z = 1.0 + r2 * ((1.0 - self.radius * self.radius / distance_squared).sqrt() - 1.0);
+
+        let phi = 2.0 * PI * r1;
+        let x = phi.cos() * (1.0 - z * z).sqrt();
+        let y = phi.sin() * (1.0 - z * z).sqrt();
+
+        basis.local(x, y, z)
+    }
+
+    fn sample_surface(&self) -> Option<(HitRecord, f64)> {
+        let outward_normal = Vec3::random_unit_vector();
+        let (u, v) = sphere_uv(outward_normal);
+        let record = HitRecord {
+            point: self.center + outward_normal * self.radius,
+            normal: outward_normal,
+            tangent: sphere_tangent(outward_normal),
+            material: self.material.clone(),
+            u,
+            v,
+            uv_scale: sphere_uv_scale(outward_normal, self.radius),
+            front_face: true,
+            ..HitRecord::default()
+        };
+
+        Some((record, 1.0 / (4.0 * PI * self.radius * self.radius)))
+    }
+
+    fn surface_pdf(&self, point: Point3) -> f64 {
+        let radius = self.radius.abs();
+        if ((point - self.center).length() - radius).abs() <= SURFACE_TOLERANCE * radius {
+            1.0 / (4.0 * PI * self.radius * self.radius)
+        } else {
+            0.0
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let radius_vector = Vec3::new(self.radius, self.radius, self.radius);
+        Aabb::from_points(self.center - radius_vector, self.center + radius_vector)
+    }
+}
+
+fn sphere_uv(p: Point3) -> (f64, f64) {
+    let theta = (-p.y()).acos();
+    let phi = (-p.z()).atan2(p.x()) + PI;
+
+    (phi / (2.0 * PI), theta / PI)
+}
+
+fn sphere_uv_scale(p: Point3, radius: f64) -> (f64, f64) {
+    let radius = radius.abs();
+    (
+        2.0 * PI * radius * (1.0 - p.y() * p.y()).max(0.0).sqrt(),
+        PI * radius,
+    )
+}
+
+// Direction of increasing u, which is undefined at the poles.
+fn sphere_tangent(p: Point3) -> Vec3 {
+    Vec3::new(p.z(), 0.0, -p.x()).unit_vector()
 }
@@ -0,0 +1,650 @@
+use crate::material::Material;
+use crate::mesh::{edge_key, vertex_normals, Mesh, TriangleMesh};
+use crate::vec3::{Point3, Vec3};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SubdivisionScheme {
+    // Loop subdivision, for triangle meshes; other polygons are fan-triangulated first.
+    Loop,
+    // Catmull–Clark subdivision, for arbitrary polygons, producing quads.
+    CatmullClark,
+}
+
+// Refines a control mesh `levels` times. Boundary edges, edges shared by more than two faces and
+// edges marked with `Mesh::add_crease` stay sharp, and vertices where three or more sharp edges
+// meet are corners.
+pub fn subdivide(mesh: &Mesh, scheme: SubdivisionScheme, levels: u32) -> Mesh {
+    let mut refined = match scheme {
+        SubdivisionScheme::Loop => triangulated(mesh),
+        SubdivisionScheme::CatmullClark => mesh.clone(),
+    };
+
+    for _ in 0..levels {
+        refined = match scheme {
+            SubdivisionScheme::Loop => loop_step(&refined),
+            SubdivisionScheme::CatmullClark => catmull_clark_step(&refined),
+        };
+    }
+
+    refined
+}
+
+// Subdivides the mesh, moves every vertex to where it lies on the limit surface, and builds a
+// renderable mesh whose smooth normals are taken from that limit surface. Vertices on creases and
+// boundaries, and any the schemes' tangent masks don't cover, get averaged face normals instead.
+pub fn limit_surface(
+    mesh: &Mesh,
+    scheme: SubdivisionScheme,
+    levels: u32,
+    material: Arc<dyn Material>,
+) -> TriangleMesh {
+    let refined = subdivide(mesh, scheme, levels);
+    let topology = Topology::new(&refined);
+
+    let positions: Vec<Point3> = (0..refined.positions().len())
+        .map(|vertex| limit_position(&refined, &topology, scheme, vertex))
+        .collect();
+
+    let triangles = refined.triangles();
+    let normals = vertex_normals(&positions, &triangles)
+        .into_iter()
+        .enumerate()
+        .map(|(vertex, averaged)| {
+            limit_normal(&refined, &topology, scheme, vertex).unwrap_or(averaged)
+        })
+        .collect();
+
+    TriangleMesh::new(
+        positions,
+        normals,
+        refined.uvs().to_vec(),
+        triangles,
+        material,
+    )
+}
+
+struct Topology {
+    edge_faces: HashMap<(usize, usize), Vec<usize>>,
+    vertex_faces: Vec<Vec<usize>>,
+    vertex_neighbors: Vec<Vec<usize>>,
+}
+
+impl Topology {
+    fn new(mesh: &Mesh) -> Self {
+        let vertex_count = mesh.positions().len();
+        let mut edge_faces: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        let mut vertex_faces = vec![Vec::new(); vertex_count];
+        let mut vertex_neighbors = vec![Vec::new(); vertex_count];
+
+        for (index, face) in mesh.faces().iter().enumerate() {
+            for (i, &vertex) in face.iter().enumerate() {
+                let next = face[(i + 1) % face.len()];
+                let faces = edge_faces.entry(edge_key(vertex, next)).or_default();
+                if faces.is_empty() {
+                    vertex_neighbors[vertex].push(next);
+                    vertex_neighbors[next].push(vertex);
+                }
+                faces.push(index);
+                vertex_faces[vertex].push(index);
+            }
+        }
+
+        Self {
+            edge_faces,
+            vertex_faces,
+            vertex_neighbors,
+        }
+    }
+
+    fn is_sharp(&self, mesh: &Mesh, a: usize, b: usize) -> bool {
+        mesh.is_crease(a, b) || self.edge_faces[&edge_key(a, b)].len() != 2
+    }
+
+    fn sharp_neighbors(&self, mesh: &Mesh, vertex: usize) -> Vec<usize> {
+        self.vertex_neighbors[vertex]
+            .iter()
+            .copied()
+            .filter(|&neighbor| self.is_sharp(mesh, vertex, neighbor))
+            .collect()
+    }
+}
+
+// Accumulates positions and texture coordinates together so both follow the same masks.
+#[derive(Copy, Clone, Debug)]
+struct Sample {
+    position: Point3,
+    uv: (f64, f64),
+}
+
+impl Sample {
+    fn zero() -> Self {
+        Self {
+            position: Point3::zero(),
+            uv: (0.0, 0.0),
+        }
+    }
+
+    fn of(mesh: &Mesh, vertex: usize) -> Self {
+        Self {
+            position: mesh.positions()[vertex],
+            uv: mesh.uvs().get(vertex).copied().unwrap_or((0.0, 0.0)),
+        }
+    }
+
+    fn add(self, other: Self, weight: f64) -> Self {
+        Self {
+            position: self.position + other.position * weight,
+            uv: (
+                self.uv.0 + other.uv.0 * weight,
+                self.uv.1 + other.uv.1 * weight,
+            ),
+        }
+    }
+
+    fn average(mesh: &Mesh, vertices: &[usize]) -> Self {
+        let weight = 1.0 / vertices.len() as f64;
+        vertices.iter().fold(Self::zero(), |sum, &vertex| {
+            sum.add(Self::of(mesh, vertex), weight)
+        })
+    }
+}
+
+fn build_mesh(samples: Vec<Sample>, faces: Vec<Vec<usize>>, has_uvs: bool) -> Mesh {
+    let (positions, uvs): (Vec<Point3>, Vec<(f64, f64)>) = samples
+        .into_iter()
+        .map(|sample| (sample.position, sample.uv))
+        .unzip();
+
+    let mut mesh = Mesh::new(positions, faces);
+    if has_uvs {
+        mesh.set_uvs(uvs);
+    }
+    mesh
+}
+
+fn triangulated(mesh: &Mesh) -> Mesh {
+    let mut triangulated = Mesh::new(
+        mesh.positions().to_vec(),
+        mesh.triangles().into_iter().map(|t| t.to_vec()).collect(),
+    );
+    if !mesh.uvs().is_empty() {
+        triangulated.set_uvs(mesh.uvs().to_vec());
+    }
+
+    for face in mesh.faces() {
+        for (i, &vertex) in face.iter().enumerate() {
+            let next = face[(i + 1) % face.len()];
+            if mesh.is_crease(vertex, next) {
+                triangulated.add_crease(vertex, next);
+            }
+        }
+    }
+
+    triangulated
+}
+
+// Shared vertex rules: smooth vertices use the scheme's mask, crease vertices the cubic
+// B-spline curve rule along their two sharp edges, and corners stay put.
+fn crease_vertex(mesh: &Mesh, vertex: usize, sharp: &[usize]) -> Option<Sample> {
+    match sharp.len() {
+        0 | 1 => None,
+        2 => Some(
+            Sample::zero()
+                .add(Sample::of(mesh, vertex), 0.75)
+                .add(Sample::of(mesh, sharp[0]), 0.125)
+                .add(Sample::of(mesh, sharp[1]), 0.125),
+        ),
+        _ => Some(Sample::of(mesh, vertex)),
+    }
+}
+
+fn catmull_clark_step(mesh: &Mesh) -> Mesh {
+    let topology = Topology::new(mesh);
+    let vertex_count = mesh.positions().len();
+    let face_count = mesh.faces().len();
+
+    let face_points: Vec<Sample> = mesh
+        .faces()
+        .iter()
+        .map(|face| Sample::average(mesh, face))
+        .collect();
+
+    let mut edge_indices = HashMap::new();
+    let mut edge_points = Vec::new();
+    for face in mesh.faces() {
+        for (i, &a) in face.iter().enumerate() {
+            let b = face[(i + 1) % face.len()];
+            let key = edge_key(a, b);
+            if edge_indices.contains_key(&key) {
+                continue;
+            }
+
+            let point = if topology.is_sharp(mesh, a, b) {
+                Sample::average(mesh, &[a, b])
+            } else {
+                let faces = &topology.edge_faces[&key];
+                Sample::zero()
+                    .add(Sample::of(mesh, a), 0.25)
+                    .add(Sample::of(mesh, b), 0.25)
+                    .add(face_points[faces[0]], 0.25)
+                    .add(face_points[faces[1]], 0.25)
+            };
+
+            edge_indices.insert(key, vertex_count + face_count + edge_points.len());
+            edge_points.push(point);
+        }
+    }
+
+    let vertex_points = (0..vertex_count).map(|vertex| {
+        let sharp = topology.sharp_neighbors(mesh, vertex);
+        crease_vertex(mesh, vertex, &sharp).unwrap_or_else(|| {
+            let neighbors = &topology.vertex_neighbors[vertex];
+            let faces = &topology.vertex_faces[vertex];
+            if neighbors.is_empty() {
+                return Sample::of(mesh, vertex);
+            }
+
+            let n = neighbors.len() as f64;
+            let face_average = faces.iter().fold(Sample::zero(), |sum, &face| {
+                sum.add(face_points[face], 1.0 / faces.len() as f64)
+            });
+            let edge_average = neighbors.iter().fold(Sample::zero(), |sum, &neighbor| {
+                sum.add(Sample::average(mesh, &[vertex, neighbor]), 1.0 / n)
+            });
+
+            Sample::zero()
+                .add(face_average, 1.0 / n)
+                .add(edge_average, 2.0 / n)
+                .add(Sample::of(mesh, vertex), (n - 3.0) / n)
+        })
+    });
+
+    let samples: Vec<Sample> = vertex_points
+        .chain(face_points.iter().copied())
+        .chain(edge_points)
+        .collect();
+
+    let mut faces = Vec::new();
+    for (index, face) in mesh.faces().iter().enumerate() {
+        for (i, &vertex) in face.iter().enumerate() {
+            let next = face[(i + 1) % face.len()];
+            let previous = face[(i + face.len() - 1) % face.len()];
+            faces.push(vec![
+                vertex,
+                edge_indices[&edge_key(vertex, next)],
+                vertex_count + index,
+                edge_indices[&edge_key(previous, vertex)],
+            ]);
+        }
+    }
+
+    let mut refined = build_mesh(samples, faces, !mesh.uvs().is_empty());
+    propagate_creases(mesh, &edge_indices, &mut refined);
+    refined
+}
+
+fn loop_step(mesh: &Mesh) -> Mesh {
+    let topology = Topology::new(mesh);
+    let vertex_count = mesh.positions().len();
+
+    let mut edge_indices = HashMap::new();
+    let mut edge_points = Vec::new();
+    for face in mesh.faces() {
+        for i in 0..3 {
+            let (a, b) = (face[i], face[(i + 1) % 3]);
+            let key = edge_key(a, b);
+            if edge_indices.contains_key(&key) {
+                continue;
+            }
+
+            let point = if topology.is_sharp(mesh, a, b) {
+                Sample::average(mesh, &[a, b])
+            } else {
+                let opposite = topology.edge_faces[&key].iter().map(|&f| {
+                    let face = &mesh.faces()[f];
+                    face.iter().copied().find(|&v| v != a && v != b).unwrap()
+                });
+                opposite.fold(
+                    Sample::zero()
+                        .add(Sample::of(mesh, a), 0.375)
+                        .add(Sample::of(mesh, b), 0.375),
+                    |sum, vertex| sum.add(Sample::of(mesh, vertex), 0.125),
+                )
+            };
+
+            edge_indices.insert(key, vertex_count + edge_points.len());
+            edge_points.push(point);
+        }
+    }
+
+    let vertex_points = (0..vertex_count).map(|vertex| {
+        let sharp = topology.sharp_neighbors(mesh, vertex);
+        crease_vertex(mesh, vertex, &sharp).unwrap_or_else(|| {
+            let neighbors = &topology.vertex_neighbors[vertex];
+            if neighbors.is_empty() {
+                return Sample::of(mesh, vertex);
+            }
+
+            let beta = loop_beta(neighbors.len());
+            neighbors.iter().fold(
+                Sample::zero().add(
+                    Sample::of(mesh, vertex),
+                    1.0 - neighbors.len() as f64 * beta,
+                ),
+                |sum, &neighbor| sum.add(Sample::of(mesh, neighbor), beta),
+            )
+        })
+    });
+
+    let samples: Vec<Sample> = vertex_points.chain(edge_points).collect();
+
+    let mut faces = Vec::new();
+    for face in mesh.faces() {
+        let [a, b, c] = [face[0], face[1], face[2]];
+        let ab = edge_indices[&edge_key(a, b)];
+        let bc = edge_indices[&edge_key(b, c)];
+        let ca = edge_indices[&edge_key(c, a)];
+
+        faces.push(vec![a, ab, ca]);
+        faces.push(vec![b, bc, ab]);
+        faces.push(vec![c, ca, bc]);
+        faces.push(vec![ab, bc, ca]);
+    }
+
+    let mut refined = build_mesh(samples, faces, !mesh.uvs().is_empty());
+    propagate_creases(mesh, &edge_indices, &mut refined);
+    refined
+}
+
+fn propagate_creases(
+    mesh: &Mesh,
+    edge_indices: &HashMap<(usize, usize), usize>,
+    refined: &mut Mesh,
+) {
+    for (&(a, b), &midpoint) in edge_indices {
+        if mesh.is_crease(a, b) {
+            refined.add_crease(a, midpoint);
+            refined.add_crease(midpoint, b);
+        }
+    }
+}
+
+fn loop_beta(valence: usize) -> f64 {
+    let n = valence as f64;
+    let c = 0.375 + 0.25 * (2.0 * PI / n).cos();
+    (0.625 - c * c) / n
+}
+
+fn limit_position(
+    mesh: &Mesh,
+    topology: &Topology,
+    scheme: SubdivisionScheme,
+    vertex: usize,
+) -> Point3 {
+    let position = mesh.positions()[vertex];
+    let neighbors = &topology.vertex_neighbors[vertex];
+    let sharp = topology.sharp_neighbors(mesh, vertex);
+
+    match sharp.len() {
+        0 | 1 if !neighbors.is_empty() => {}
+        2 => {
+            return (mesh.positions()[sharp[0]] + position * 4.0 + mesh.positions()[sharp[1]]) / 6.0
+        }
+        _ => return position,
+    }
+
+    let n = neighbors.len() as f64;
+    let neighbor_sum = neighbors.iter().fold(Point3::zero(), |sum, &neighbor| {
+        sum + mesh.positions()[neighbor]
+    });
+
+    match scheme {
+        SubdivisionScheme::Loop => {
+            let omega = 3.0 / (8.0 * loop_beta(neighbors.len()));
+            (position * omega + neighbor_sum) / (omega + n)
+        }
+        SubdivisionScheme::CatmullClark => {
+            let mut diagonal_sum = Point3::zero();
+            for &face in &topology.vertex_faces[vertex] {
+                let face = &mesh.faces()[face];
+                if face.len() != 4 {
+                    return position;
+                }
+                let corner = face.iter().position(|&v| v == vertex).unwrap();
+                diagonal_sum += mesh.positions()[face[(corner + 2) % 4]];
+            }
+
+            (position * (n * n) + neighbor_sum * 4.0 + diagonal_sum) / (n * (n + 5.0))
+        }
+    }
+}
+
+// The neighbors of a smooth vertex in order around it, following the winding of its faces, each
+// paired with the vertex diagonally across the quad that follows it. None unless the faces close
+// a single fan around the vertex.
+fn one_ring(
+    mesh: &Mesh,
+    topology: &Topology,
+    vertex: usize,
+) -> Option<Vec<(usize, Option<usize>)>> {
+    if !topology.sharp_neighbors(mesh, vertex).is_empty() {
+        return None;
+    }
+
+    // Each face around the vertex leads from the neighbor after it to the neighbor before it.
+    let faces = &topology.vertex_faces[vertex];
+    let mut steps = HashMap::new();
+    for &face in faces {
+        let face = &mesh.faces()[face];
+        let corner = face.iter().position(|&v| v == vertex)?;
+        let next = face[(corner + 1) % face.len()];
+        let previous = face[(corner + face.len() - 1) % face.len()];
+        let diagonal = (face.len() == 4).then(|| face[(corner + 2) % 4]);
+        steps.insert(next, (previous, diagonal));
+    }
+
+    let first = *steps.keys().next()?;
+    let mut ring = Vec::with_capacity(faces.len());
+    let mut current = first;
+    loop {
+        let &(previous, diagonal) = steps.get(&current)?;
+        ring.push((current, diagonal));
+        current = previous;
+        if current == first {
+            break;
+        }
+        if ring.len() > faces.len() {
+            return None;
+        }
+    }
+
+    (ring.len() == faces.len()).then_some(ring)
+}
+
+// Normal of the limit surface at a smooth vertex, from the cross product of the scheme's limit
+// tangent masks over its one-ring.
+fn limit_normal(
+    mesh: &Mesh,
+    topology: &Topology,
+    scheme: SubdivisionScheme,
+    vertex: usize,
+) -> Option<Vec3> {
+    let ring = one_ring(mesh, topology, vertex)?;
+    let n = ring.len() as f64;
+    let angle = |i: usize| 2.0 * PI * i as f64 / n;
+    let position = |vertex: usize| mesh.positions()[vertex];
+
+    let mut tangent_u = Vec3::zero();
+    let mut tangent_v = Vec3::zero();
+    match scheme {
+        SubdivisionScheme::Loop => {
+            for (i, &(neighbor, _)) in ring.iter().enumerate() {
+                tangent_u += position(neighbor) * angle(i).cos();
+                tangent_v += position(neighbor) * angle(i).sin();
+            }
+        }
+        // The masks of Halstead, Kass and DeRose, which need every face around the vertex to
+        // be a quad.
+        SubdivisionScheme::CatmullClark => {
+            let edge_weight =
+                1.0 + angle(1).cos() + (PI / n).cos() * (2.0 * (9.0 + angle(1).cos())).sqrt();
+            for (i, &(neighbor, diagonal)) in ring.iter().enumerate() {
+                let diagonal = position(diagonal?);
+                tangent_u += position(neighbor) * (edge_weight * angle(i).cos())
+                    + diagonal * (angle(i).cos() + angle(i + 1).cos());
+                tangent_v += position(neighbor) * (edge_weight * angle(i).sin())
+                    + diagonal * (angle(i).sin() + angle(i + 1).sin());
+            }
+        }
+    }
+
+    let normal = tangent_u.cross(tangent_v);
+    (!normal.near_zero()).then(|| normal.unit_vector())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+
+    const GRID_SIZE: usize = 5;
+
+    fn grid_vertex(i: usize, j: usize) -> usize {
+        i + GRID_SIZE * j
+    }
+
+    // A flat grid of unit quads facing +z, with only the middle vertex raised to `height`.
+    fn grid(height: f64) -> Mesh {
+        let middle = GRID_SIZE / 2;
+        let positions = (0..GRID_SIZE * GRID_SIZE)
+            .map(|index| {
+                let (i, j) = (index % GRID_SIZE, index / GRID_SIZE);
+                let z = if (i, j) == (middle, middle) {
+                    height
+                } else {
+                    0.0
+                };
+                Point3::new(i as f64, j as f64, z)
+            })
+            .collect();
+
+        let mut faces = Vec::new();
+        for j in 0..GRID_SIZE - 1 {
+            for i in 0..GRID_SIZE - 1 {
+                faces.push(vec![
+                    grid_vertex(i, j),
+                    grid_vertex(i + 1, j),
+                    grid_vertex(i + 1, j + 1),
+                    grid_vertex(i, j + 1),
+                ]);
+            }
+        }
+
+        Mesh::new(positions, faces)
+    }
+
+    fn limit_height(scheme: SubdivisionScheme, i: usize, j: usize) -> f64 {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        limit_surface(&grid(1.0), scheme, 0, material).positions()[grid_vertex(i, j)].z()
+    }
+
+    // Regular Catmull–Clark vertices land on the uniform bicubic B-spline, which weighs the
+    // vertex, its edge neighbors and its diagonal neighbors by 16, 4 and 1 out of 36.
+    #[test]
+    fn catmull_clark_limit_of_a_regular_grid() {
+        let middle = GRID_SIZE / 2;
+        let scheme = SubdivisionScheme::CatmullClark;
+        assert!((limit_height(scheme, middle, middle) - 16.0 / 36.0).abs() < 1e-12);
+        assert!((limit_height(scheme, middle + 1, middle) - 4.0 / 36.0).abs() < 1e-12);
+        assert!((limit_height(scheme, middle + 1, middle + 1) - 1.0 / 36.0).abs() < 1e-12);
+    }
+
+    // Quads are split along the same diagonal, giving regular valence six vertices, whose Loop
+    // limit weighs the vertex by a half and each neighbor by a twelfth.
+    #[test]
+    fn loop_limit_of_a_regular_grid() {
+        let middle = GRID_SIZE / 2;
+        let scheme = SubdivisionScheme::Loop;
+        assert!((limit_height(scheme, middle, middle) - 0.5).abs() < 1e-12);
+        assert!((limit_height(scheme, middle + 1, middle + 1) - 1.0 / 12.0).abs() < 1e-12);
+        assert!(limit_height(scheme, middle + 1, middle - 1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn limit_normals_of_a_flat_grid_face_up() {
+        let mesh = grid(0.0);
+        for scheme in [SubdivisionScheme::Loop, SubdivisionScheme::CatmullClark] {
+            let refined = subdivide(&mesh, scheme, 1);
+            let middle = refined
+                .positions()
+                .iter()
+                .position(|&p| (p - Point3::new(2.0, 2.0, 0.0)).near_zero())
+                .unwrap();
+            let normal = limit_normal(&refined, &Topology::new(&refined), scheme, middle).unwrap();
+            assert!(
+                (normal - Vec3::new(0.0, 0.0, 1.0)).near_zero(),
+                "{normal:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn subdivision_counts_of_a_cube() {
+        let positions = (0..8)
+            .map(|i| Point3::new((i & 1) as f64, ((i >> 1) & 1) as f64, (i >> 2) as f64))
+            .collect();
+        let faces = vec![
+            vec![0, 2, 3, 1],
+            vec![4, 5, 7, 6],
+            vec![0, 1, 5, 4],
+            vec![2, 6, 7, 3],
+            vec![0, 4, 6, 2],
+            vec![1, 3, 7, 5],
+        ];
+        let cube = Mesh::new(positions, faces);
+
+        // Every quad splits into four, adding a point for each face and each edge.
+        let refined = subdivide(&cube, SubdivisionScheme::CatmullClark, 1);
+        assert_eq!(refined.faces().len(), 24);
+        assert_eq!(refined.positions().len(), 8 + 6 + 12);
+
+        // Loop first splits the quads into twelve triangles, adding six diagonals.
+        let refined = subdivide(&cube, SubdivisionScheme::Loop, 1);
+        assert_eq!(refined.faces().len(), 48);
+        assert_eq!(refined.positions().len(), 8 + 12 + 6);
+    }
+
+    #[test]
+    fn edges_shared_by_three_faces_subdivide_as_creases() {
+        let positions = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.5, 1.0, 0.0),
+            Point3::new(0.5, -1.0, 0.0),
+            Point3::new(0.5, 0.0, 1.0),
+        ];
+        let mesh = Mesh::new(positions, vec![vec![0, 1, 2], vec![1, 0, 3], vec![0, 1, 4]]);
+
+        for scheme in [SubdivisionScheme::Loop, SubdivisionScheme::CatmullClark] {
+            let refined = subdivide(&mesh, scheme, 2);
+            // The shared edge stays straight, so its midpoint stays on the x axis.
+            assert!(refined
+                .positions()
+                .iter()
+                .any(|&p| (p - Point3::new(0.5, 0.0, 0.0)).near_zero()));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "face repeats a vertex")]
+    fn faces_repeating_a_vertex_are_rejected() {
+        Mesh::new(
+            vec![Point3::zero(), Point3::new(1.0, 0.0, 0.0)],
+            vec![vec![0, 0, 1]],
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use crate::color::Color;
+use crate::image::Image;
+use crate::interval::Interval;
+use crate::perlin::Perlin;
+use crate::vec3::Point3;
+use std::fmt::Debug;
+
+pub trait Texture: Debug + Send + Sync {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color;
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SolidColor {
+    albedo: Color,
+}
+
+impl SolidColor {
+    pub fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: Point3) -> Color {
+        self.albedo
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NoiseTexture {
+    noise: Perlin,
+    scale: f64,
+    octaves: u32,
+}
+
+impl NoiseTexture {
+    pub fn new(scale: f64, octaves: u32) -> Self {
+        Self {
+            noise: Perlin::new(),
+            scale,
+            octaves,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let value = self.noise.turbulence(p * self.scale, self.octaves);
+        Color::new(value, value, value)
+    }
+}
+
+// Looks up an image by texture coordinates with bilinear filtering, where v runs up from the
+// bottom row.
+#[derive(Clone, Debug)]
+pub struct ImageTexture {
+    image: Image,
+}
+
+impl ImageTexture {
+    pub fn new(image: Image) -> Self {
+        Self { image }
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: Point3) -> Color {
+        let unit = Interval::new(0.0, 1.0);
+        let x = unit.clamp(u) * (self.image.width() - 1) as f64;
+        let y = (1.0 - unit.clamp(v)) * (self.image.height() - 1) as f64;
+
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+
+        self.image.pixel(x0, y0) * ((1.0 - fx) * (1.0 - fy))
+            + self.image.pixel(x0 + 1, y0) * (fx * (1.0 - fy))
+            + self.image.pixel(x0, y0 + 1) * ((1.0 - fx) * fy)
+            + self.image.pixel(x0 + 1, y0 + 1) * (fx * fy)
+    }
+}
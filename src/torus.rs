@@ -0,0 +1,118 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::polynomial::solve_quartic;
+use crate::ray::Ray;
+use crate::utility::azimuth;
+use crate::vec3::{Point3, Vec3};
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+// A torus around `center` whose ring lies in the xz plane, so +y is its axis of symmetry.
+#[derive(Clone, Debug)]
+pub struct Torus {
+    center: Point3,
+    major_radius: f64,
+    minor_radius: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Torus {
+    pub fn new(
+        center: Point3,
+        major_radius: f64,
+        minor_radius: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+
+    pub fn center(&self) -> Point3 {
+        self.center
+    }
+
+    pub fn major_radius(&self) -> f64 {
+        self.major_radius
+    }
+
+    pub fn minor_radius(&self) -> f64 {
+        self.minor_radius
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        // Solve along a unit direction starting where the ray enters the bounding sphere, which
+        // keeps the quartic coefficients small and well conditioned.
+        let direction_length = ray.direction().length();
+        let direction = ray.direction() / direction_length;
+        let oc = ray.origin() - self.center;
+
+        let bounding_radius = self.major_radius + self.minor_radius;
+        let half_b = oc.dot(direction);
+        let discriminant =
+            half_b * half_b - (oc.length_squared() - bounding_radius * bounding_radius);
+        if discriminant < 0.0 {
+            return false;
+        }
+        let offset = (-half_b - discriminant.sqrt()).max(0.0);
+
+        let origin = oc + direction * offset;
+        let r2 = self.major_radius * self.major_radius;
+
+        let f = origin.dot(direction);
+        let m = origin.length_squared();
+        let e = m + r2 - self.minor_radius * self.minor_radius;
+
+        let roots = solve_quartic(
+            1.0,
+            4.0 * f,
+            4.0 * f * f + 2.0 * e - 4.0 * r2 + 4.0 * r2 * direction.y() * direction.y(),
+            4.0 * f * e - 8.0 * r2 * f + 8.0 * r2 * origin.y() * direction.y(),
+            e * e - 4.0 * r2 * m + 4.0 * r2 * origin.y() * origin.y(),
+        );
+
+        let Some(root) = roots
+            .into_iter()
+            .map(|distance| (distance + offset) / direction_length)
+            .find(|&root| ray_t.surrounds(root))
+        else {
+            return false;
+        };
+
+        let local = ray.at(root) - self.center;
+        let ring_distance = (local.x() * local.x() + local.z() * local.z()).sqrt();
+        let ring_point = if ring_distance > 0.0 {
+            Vec3::new(local.x(), 0.0, local.z()) * (self.major_radius / ring_distance)
+        } else {
+            Vec3::zero()
+        };
+        let outward_normal = (local - ring_point).unit_vector();
+
+        record.t = root;
+        record.point = ray.at(root);
+        record.material = self.material.clone();
+        record.set_face_normal(ray, outward_normal);
+        record.tangent = Vec3::new(-local.z(), 0.0, local.x()).unit_vector();
+        record.u = azimuth(local.x(), local.z()) / (2.0 * PI);
+        record.v = (local.y().atan2(ring_distance - self.major_radius) + PI) / (2.0 * PI);
+        record.uv_scale = (2.0 * PI * ring_distance, 2.0 * PI * self.minor_radius);
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let extents = Vec3::new(
+            self.major_radius + self.minor_radius,
+            self.minor_radius,
+            self.major_radius + self.minor_radius,
+        );
+        Aabb::from_points(self.center - extents, self.center + extents)
+    }
+}
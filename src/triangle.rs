@@ -0,0 +1,89 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::Point3;
+use std::sync::Arc;
+
+#[derive(Clone, Debug)]
+pub struct Triangle {
+    vertices: [Point3; 3],
+    material: Arc<dyn Material>,
+}
+
+impl Triangle {
+    pub fn new(a: Point3, b: Point3, c: Point3, material: Arc<dyn Material>) -> Self {
+        Self {
+            vertices: [a, b, c],
+            material,
+        }
+    }
+
+    pub fn vertices(&self) -> [Point3; 3] {
+        self.vertices
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let [a, b, c] = self.vertices;
+        let Some((t, beta, gamma)) = intersect_triangle(ray, a, b, c, ray_t) else {
+            return false;
+        };
+
+        record.t = t;
+        record.point = ray.at(t);
+        record.material = self.material.clone();
+        record.set_face_normal(ray, (b - a).cross(c - a).unit_vector());
+        record.tangent = (b - a).unit_vector();
+        record.u = beta;
+        record.v = gamma;
+        record.uv_scale = ((b - a).length(), (c - a).length());
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let [a, b, c] = self.vertices;
+        Aabb::surrounding(Aabb::from_points(a, b), Aabb::from_points(c, c))
+    }
+}
+
+// Möller–Trumbore intersection, returning the ray parameter and the barycentric weights of `b`
+// and `c`.
+pub(crate) fn intersect_triangle(
+    ray: Ray,
+    a: Point3,
+    b: Point3,
+    c: Point3,
+    ray_t: Interval,
+) -> Option<(f64, f64, f64)> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = ray.direction().cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+
+    let inverse_determinant = 1.0 / determinant;
+    let s = ray.origin() - a;
+    let beta = s.dot(p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&beta) {
+        return None;
+    }
+
+    let q = s.cross(edge1);
+    let gamma = ray.direction().dot(q) * inverse_determinant;
+    if gamma < 0.0 || beta + gamma > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(q) * inverse_determinant;
+    if !ray_t.surrounds(t) {
+        return None;
+    }
+
+    Some((t, beta, gamma))
+}
@@ -0,0 +1,71 @@
+use crate::aabb::Aabb;
+use crate::annulus::Annulus;
+use crate::cylinder::Cylinder;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use std::sync::Arc;
+
+// A thick-walled pipe along +y, optionally closed at both ends by annular caps.
+#[derive(Clone, Debug)]
+pub struct Tube {
+    outer: Cylinder,
+    inner: Cylinder,
+    caps: Option<[Annulus; 2]>,
+}
+
+impl Tube {
+    pub fn new(
+        base: Point3,
+        inner_radius: f64,
+        outer_radius: f64,
+        height: f64,
+        sweep: f64,
+        capped: bool,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let outer = Cylinder::new(base, outer_radius, height, sweep, false, material.clone());
+        let inner =
+            Cylinder::new(base, inner_radius, height, sweep, false, material.clone()).inward();
+
+        let caps = capped.then(|| {
+            [
+                Annulus::new(base, inner_radius, outer_radius, sweep, material.clone())
+                    .facing_down(),
+                Annulus::new(
+                    base + Vec3::new(0.0, height, 0.0),
+                    inner_radius,
+                    outer_radius,
+                    sweep,
+                    material,
+                ),
+            ]
+        });
+
+        Self { outer, inner, caps }
+    }
+}
+
+impl Hittable for Tube {
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let walls: [&dyn Hittable; 2] = [&self.outer, &self.inner];
+        let caps = self.caps.iter().flatten().map(|cap| cap as &dyn Hittable);
+
+        let mut hit_anything = false;
+        let mut closest_so_far = ray_t.max();
+        for surface in walls.into_iter().chain(caps) {
+            if surface.hit(ray, Interval::new(ray_t.min(), closest_so_far), record) {
+                hit_anything = true;
+                closest_so_far = record.t;
+            }
+        }
+
+        hit_anything
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.outer.bounding_box()
+    }
+}
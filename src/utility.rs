@@ -1,4 +1,4 @@
-use rand::random;
+use crate::sampler::next_sample;
 use std::f64::consts::PI;
 
 pub fn degrees_to_radians(degrees: f64) -> f64 {
@@ -6,9 +6,22 @@ pub fn degrees_to_radians(degrees: f64) -> f64 {
 }
 
 pub fn random_double() -> f64 {
-    random::<f64>()
+    next_sample()
 }
 
 pub fn random_double_range(min: f64, max: f64) -> f64 {
     min + (max - min) * random_double()
 }
+
+pub fn random_int(min: i32, max: i32) -> i32 {
+    random_double_range(min as f64, max as f64 + 1.0) as i32
+}
+
+pub fn azimuth(x: f64, z: f64) -> f64 {
+    let phi = z.atan2(x);
+    if phi < 0.0 {
+        phi + 2.0 * PI
+    } else {
+        phi
+    }
+}
@@ -0,0 +1,320 @@
+use crate::aabb::Aabb;
+use crate::color::Color;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::{Material, Medium};
+use crate::perlin::Perlin;
+use crate::phase::PhaseFunction;
+use crate::ray::Ray;
+use crate::utility::random_double;
+use crate::vec3::{Point3, Vec3};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+pub trait DensityField {
+    fn density(&self, p: Point3) -> f64;
+
+    // Upper bound on `density` anywhere inside the volume, used as the majorant for tracking.
+    fn max_density(&self) -> f64;
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ConstantDensity {
+    density: f64,
+}
+
+impl ConstantDensity {
+    pub fn new(density: f64) -> Self {
+        Self { density }
+    }
+}
+
+impl DensityField for ConstantDensity {
+    fn density(&self, _p: Point3) -> f64 {
+        self.density
+    }
+
+    fn max_density(&self) -> f64 {
+        self.density
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NoiseDensity {
+    noise: Perlin,
+    frequency: f64,
+    octaves: u32,
+    scale: f64,
+}
+
+impl NoiseDensity {
+    pub fn new(frequency: f64, octaves: u32, scale: f64) -> Self {
+        assert!(octaves >= 1);
+
+        Self {
+            noise: Perlin::new(),
+            frequency,
+            octaves,
+            scale,
+        }
+    }
+}
+
+impl DensityField for NoiseDensity {
+    fn density(&self, p: Point3) -> f64 {
+        let max_turbulence = 2.0 - 0.5_f64.powi(self.octaves as i32 - 1);
+        let turbulence = self.noise.turbulence(p * self.frequency, self.octaves);
+        self.scale * Interval::new(0.0, 1.0).clamp(turbulence / max_turbulence)
+    }
+
+    fn max_density(&self) -> f64 {
+        self.scale
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GridDensity {
+    resolution: [usize; 3],
+    values: Vec<f64>,
+    min: Point3,
+    max: Point3,
+    scale: f64,
+    max_value: f64,
+}
+
+impl GridDensity {
+    // Densities must be finite and non-negative, since tracking takes the largest as the bound
+    // on the whole field.
+    pub fn new(
+        resolution: [usize; 3],
+        values: Vec<f64>,
+        min: Point3,
+        max: Point3,
+        scale: f64,
+    ) -> Self {
+        assert!(resolution.iter().all(|&cells| cells > 0));
+        assert_eq!(values.len(), resolution[0] * resolution[1] * resolution[2]);
+        assert!(values.iter().all(|&value| is_valid_density(value)));
+
+        let max_value = values.iter().copied().fold(0.0, f64::max);
+
+        Self {
+            resolution,
+            values,
+            min,
+            max,
+            scale,
+            max_value,
+        }
+    }
+
+    // Raw grids are three little-endian u32 dimensions (x, y, z) followed by that many
+    // little-endian f32 densities, x varying fastest.
+    pub fn from_raw_file(
+        path: impl AsRef<Path>,
+        min: Point3,
+        max: Point3,
+        scale: f64,
+    ) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let invalid =
+            |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+        if bytes.len() < 12 {
+            return Err(invalid("raw grid is missing its header"));
+        }
+
+        let mut resolution = [0; 3];
+        for (axis, chunk) in bytes[..12].chunks_exact(4).enumerate() {
+            resolution[axis] = u32::from_le_bytes(chunk.try_into().unwrap()) as usize;
+        }
+
+        if resolution.contains(&0) {
+            return Err(invalid("raw grid has an empty axis"));
+        }
+
+        let size = resolution
+            .iter()
+            .try_fold(4_usize, |size, &cells| size.checked_mul(cells))
+            .and_then(|size| size.checked_add(12))
+            .ok_or_else(|| invalid("raw grid header is too large"))?;
+        if bytes.len() != size {
+            return Err(invalid("raw grid size does not match its header"));
+        }
+
+        let values: Vec<f64> = bytes[12..]
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()) as f64)
+            .collect();
+        if !values.iter().all(|&value| is_valid_density(value)) {
+            return Err(invalid("raw grid has a negative or non-finite density"));
+        }
+
+        Ok(Self::new(resolution, values, min, max, scale))
+    }
+
+    fn voxel(&self, x: usize, y: usize, z: usize) -> f64 {
+        self.values[x + self.resolution[0] * (y + self.resolution[1] * z)]
+    }
+}
+
+fn is_valid_density(value: f64) -> bool {
+    value.is_finite() && value >= 0.0
+}
+
+impl DensityField for GridDensity {
+    fn density(&self, p: Point3) -> f64 {
+        let local = (p - self.min) / (self.max - self.min);
+        let coordinates = [local.x(), local.y(), local.z()];
+
+        let mut base = [0; 3];
+        let mut fraction = [0.0; 3];
+        for axis in 0..3 {
+            if !(0.0..=1.0).contains(&coordinates[axis]) {
+                return 0.0;
+            }
+
+            let cells = self.resolution[axis].saturating_sub(1);
+            let position = coordinates[axis] * cells as f64;
+            base[axis] = (position.floor() as usize).min(cells.saturating_sub(1));
+            fraction[axis] = position - base[axis] as f64;
+        }
+
+        let mut accumulated = 0.0;
+        for corner in 0..8 {
+            let mut index = [0; 3];
+            let mut weight = 1.0;
+            for axis in 0..3 {
+                let offset = (corner >> axis) & 1;
+                index[axis] = (base[axis] + offset).min(self.resolution[axis] - 1);
+                weight *= if offset == 1 {
+                    fraction[axis]
+                } else {
+                    1.0 - fraction[axis]
+                };
+            }
+            accumulated += weight * self.voxel(index[0], index[1], index[2]);
+        }
+
+        self.scale * accumulated
+    }
+
+    fn max_density(&self) -> f64 {
+        self.scale * self.max_value
+    }
+}
+
+pub struct Volume<B: Hittable, D: DensityField> {
+    boundary: B,
+    density: D,
+    material: Arc<dyn Material>,
+}
+
+impl<B: Hittable, D: DensityField> Volume<B, D> {
+    pub fn new(boundary: B, density: D, albedo: Color, phase_function: PhaseFunction) -> Self {
+        Self {
+            boundary,
+            density,
+            material: Arc::new(Medium::new(albedo, phase_function)),
+        }
+    }
+
+    fn segment(&self, ray: Ray, ray_t: Interval) -> Option<Interval> {
+        let mut entry = HitRecord::default();
+        let mut exit = HitRecord::default();
+
+        if !self.boundary.hit(ray, Interval::universe(), &mut entry) {
+            return None;
+        }
+
+        if !self.boundary.hit(
+            ray,
+            Interval::new(entry.t + 0.0001, f64::INFINITY),
+            &mut exit,
+        ) {
+            return None;
+        }
+
+        let t_min = entry.t.max(ray_t.min()).max(0.0);
+        let t_max = exit.t.min(ray_t.max());
+        if t_min >= t_max {
+            return None;
+        }
+
+        Some(Interval::new(t_min, t_max))
+    }
+}
+
+impl<B: Hittable, D: DensityField> Hittable for Volume<B, D> {
+    // Delta tracking: sample free flights against the majorant and accept each tentative
+    // collision with probability density / majorant.
+    fn hit(&self, ray: Ray, ray_t: Interval, record: &mut HitRecord) -> bool {
+        let Some(segment) = self.segment(ray, ray_t) else {
+            return false;
+        };
+
+        let majorant = self.density.max_density();
+        if majorant <= 0.0 {
+            return false;
+        }
+
+        let ray_length = ray.direction().length();
+        let mut t = segment.min();
+        loop {
+            t += free_flight_distance(majorant) / ray_length;
+            if t >= segment.max() {
+                return false;
+            }
+
+            if random_double() * majorant < self.density.density(ray.at(t)) {
+                break;
+            }
+        }
+
+        record.t = t;
+        record.point = ray.at(t);
+        record.normal = Vec3::new(1.0, 0.0, 0.0);
+        record.tangent = Vec3::zero();
+        record.uv_scale = (1.0, 1.0);
+        record.front_face = true;
+        record.material = self.material.clone();
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+
+    // Ratio tracking: rather than stopping at the first collision, every tentative collision
+    // scales the estimate by the chance of it being a null one, which is far less noisy for
+    // shadow rays than delta tracking's all-or-nothing answer.
+    fn transmittance(&self, ray: Ray, ray_t: Interval) -> f64 {
+        let Some(segment) = self.segment(ray, ray_t) else {
+            return 1.0;
+        };
+
+        let majorant = self.density.max_density();
+        if majorant <= 0.0 {
+            return 1.0;
+        }
+
+        let ray_length = ray.direction().length();
+        let mut transmittance = 1.0;
+        let mut t = segment.min();
+        loop {
+            t += free_flight_distance(majorant) / ray_length;
+            if t >= segment.max() {
+                return transmittance;
+            }
+
+            transmittance *= 1.0 - self.density.density(ray.at(t)) / majorant;
+        }
+    }
+}
+
+fn free_flight_distance(majorant: f64) -> f64 {
+    -(1.0 - random_double()).ln() / majorant
+}
@@ -0,0 +1,44 @@
+use riaw::bdpt::BidirectionalPathTracer;
+use riaw::integrator::{Integrator, PathTracer};
+use riaw::mlt::MetropolisLightTransport;
+use riaw::sampler::seed;
+use riaw::scenes::lit_room;
+
+const MAX_DEPTH: u32 = 16;
+// Unbiased integrators should agree on the room's brightness to within their noise at these
+// sample counts.
+const TOLERANCE: f64 = 0.08;
+
+// Mean brightness of a small, repeatable render of the lit room.
+fn mean_luminance(integrator: &dyn Integrator, samples_per_pixel: u32) -> f64 {
+    seed(1);
+    let (scene, camera) = lit_room(16, samples_per_pixel);
+    integrator.render(&camera, &scene).average().luminance()
+}
+
+#[test]
+fn bidirectional_matches_path_tracing() {
+    let reference = mean_luminance(&PathTracer::new(MAX_DEPTH), 512);
+    let bidirectional = mean_luminance(&BidirectionalPathTracer::new(MAX_DEPTH), 128);
+
+    assert!(
+        (bidirectional / reference - 1.0).abs() < TOLERANCE,
+        "bidirectional {bidirectional} against path traced {reference}"
+    );
+}
+
+// Metropolis only redistributes light across the image, so its brightness comes from the
+// bootstrap, which gets as many paths as the reference render.
+#[test]
+fn metropolis_matches_path_tracing() {
+    let reference = mean_luminance(&PathTracer::new(MAX_DEPTH), 512);
+    let metropolis = mean_luminance(
+        &MetropolisLightTransport::new(MAX_DEPTH, 16 * 16 * 512, 16, 0.01, 0.3),
+        32,
+    );
+
+    assert!(
+        (metropolis / reference - 1.0).abs() < TOLERANCE,
+        "metropolis {metropolis} against path traced {reference}"
+    );
+}